@@ -1,15 +1,24 @@
+//! Camara orbital del visor.
 
 use nalgebra_glm::{Vec3, rotate_vec3};
 use std::f32::consts::PI;
 
+/// Camara orbital: `ojo` mira hacia `centro` con `sube` como eje vertical.
+/// `camb` se pone en verdadero cada vez que la camara se mueve.
+#[derive(Clone)]
 pub struct Camera {
+  /// Posicion de la camara.
   pub ojo: Vec3,
+  /// Punto al que mira y alrededor del cual orbita.
   pub centro: Vec3,
+  /// Direccion de arriba.
   pub sube: Vec3,
+  /// Se pone en verdadero cada vez que la camara se mueve.
   pub camb: bool
 }
 
 impl Camera {
+  /// Camara en `ojo` mirando a `centro`, con `coo_arr` como direccion de arriba.
   pub fn new(ojo: Vec3, centro: Vec3, coo_arr: Vec3) -> Self {
     Camera {
       ojo,
@@ -21,6 +30,9 @@ impl Camera {
 
 
 
+  /// Gira el ojo alrededor de `centro` sin cambiar la distancia: `delta_yaw`
+  /// alrededor del eje y y `delta_pitch` hacia arriba o abajo, sin llegar a
+  /// los polos.
   pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
     let radius_vector = self.ojo - self.centro;
     let radius = radius_vector.magnitude();
@@ -43,12 +55,17 @@ impl Camera {
     self.camb = true;
   }
 
+  /// Acerca el ojo a `centro` una distancia `delta`; con `delta` negativo lo
+  /// aleja.
   pub fn zoom(&mut self, delta: f32) {
     let direction = (self.centro - self.ojo).normalize();
     self.ojo += direction * delta;
     self.camb = true;
   }
 
+  /// Gira la direccion de vista con el ojo fijo: `direction.x` hacia los lados
+  /// y `direction.y` hacia arriba o abajo. El centro se mueve con ella y queda
+  /// a la misma distancia.
   pub fn move_center(&mut self, direction: Vec3) {
     let radius_vector = self.centro - self.ojo;
     let radius = radius_vector.magnitude();
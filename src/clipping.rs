@@ -0,0 +1,80 @@
+//! Recorte de triangulos contra el frustum en clip space y paso a
+//! coordenadas de pantalla.
+
+use nalgebra_glm::{Vec3, Vec4, Mat4};
+use crate::vertex::Vertex;
+
+// Planos del frustum en clip space, como coeficientes (x, y, z, w).
+// Un vertice esta adentro cuando dot(plano, clip_position) >= 0,
+// es decir -w <= x, y, z <= w.
+const PLANOS: [Vec4; 6] = [
+    Vec4::new(0.0, 0.0, 1.0, 1.0),   // near
+    Vec4::new(0.0, 0.0, -1.0, 1.0),  // far
+    Vec4::new(1.0, 0.0, 0.0, 1.0),   // left
+    Vec4::new(-1.0, 0.0, 0.0, 1.0),  // right
+    Vec4::new(0.0, 1.0, 0.0, 1.0),   // bottom
+    Vec4::new(0.0, -1.0, 0.0, 1.0),  // top
+];
+
+/// `true` si el vertice esta adentro de los seis planos del frustum. Un
+/// triangulo con los tres vertices adentro no necesita `clip_triangle`.
+pub fn inside_frustum(vertex: &Vertex) -> bool {
+    PLANOS.iter().all(|plano| plano.dot(&vertex.clip_position) >= 0.0)
+}
+
+/// Recorta un triangulo en clip space contra los seis planos del frustum
+/// (Sutherland-Hodgman). Devuelve el poligono convexo resultante, vacio si
+/// el triangulo queda completamente afuera. Los vertices nuevos interpolan
+/// todos sus atributos.
+pub fn clip_triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Vertex> {
+    let mut polygon = vec![v1.clone(), v2.clone(), v3.clone()];
+    if polygon.iter().all(inside_frustum) {
+        return polygon;
+    }
+
+    for plano in PLANOS.iter() {
+        if polygon.is_empty() {
+            break;
+        }
+
+        let mut output = Vec::with_capacity(polygon.len() + 1);
+        for i in 0..polygon.len() {
+            let actual = &polygon[i];
+            let siguiente = &polygon[(i + 1) % polygon.len()];
+            let d_actual = plano.dot(&actual.clip_position);
+            let d_siguiente = plano.dot(&siguiente.clip_position);
+
+            if d_actual >= 0.0 {
+                output.push(actual.clone());
+            }
+            if (d_actual >= 0.0) != (d_siguiente >= 0.0) {
+                // Siempre se interpola del vertice de adentro al de afuera para
+                // que dos triangulos que comparten la arista generen
+                // exactamente el mismo vertice y no aparezcan grietas.
+                if d_actual >= 0.0 {
+                    output.push(actual.lerp(siguiente, d_actual / (d_actual - d_siguiente)));
+                } else {
+                    output.push(siguiente.lerp(actual, d_siguiente / (d_siguiente - d_actual)));
+                }
+            }
+        }
+        polygon = output;
+    }
+
+    polygon
+}
+
+/// Aplica la division perspectiva y la matriz de viewport a un vertice ya
+/// recortado, dejando el resultado en `transformed_position`. Guarda `1/w`
+/// para que el rasterizador pueda interpolar con correccion de perspectiva.
+pub fn to_screen(vertex: &Vertex, viewport_matrix: &Mat4) -> Vertex {
+    let clip = vertex.clip_position;
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = viewport_matrix * ndc;
+
+    Vertex {
+        transformed_position: Vec3::new(screen.x, screen.y, screen.z),
+        inv_w: 1.0 / clip.w,
+        ..vertex.clone()
+    }
+}
@@ -1,5 +1,9 @@
+//! Colores RGB de 8 bits por canal.
+
 use std::fmt;
+use nalgebra_glm::Vec3;
 
+/// Color RGB de 8 bits por canal.
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
     r: u8,
@@ -8,19 +12,23 @@ pub struct Color {
 }
 
 impl Color {
+    /// Color con esos valores de rojo, verde y azul.
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Color { r, g, b }
     }
 
+    /// Negro.
     pub const fn black() -> Self {
         Color { r: 0, g: 0, b: 0 }
     }
 
-    pub fn to_hex(&self) -> u32 {
+    /// El color como `0xRRGGBB`, el formato del framebuffer.
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
     
+    /// Interpolacion lineal hacia `other`; `t` se limita a [0, 1].
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
         Color {
@@ -30,6 +38,30 @@ impl Color {
         }
     }
 
+    /// Multiplica cada canal por su propio factor, como `color * f32` por
+    /// canal. Sirve para aplicar la luz acumulada de cada canal.
+    pub fn scale_rgb(self, factor: Vec3) -> Self {
+        Color {
+            r: (self.r as f32 * factor.x).clamp(0.0, 255.0) as u8,
+            g: (self.g as f32 * factor.y).clamp(0.0, 255.0) as u8,
+            b: (self.b as f32 * factor.z).clamp(0.0, 255.0) as u8,
+        }
+    }
+
+    /// Los canales en [0, 1].
+    pub fn to_vec3(self) -> Vec3 {
+        Vec3::new(self.r as f32, self.g as f32, self.b as f32) / 255.0
+    }
+
+    /// Color a partir de canales en [0, 1]; lo que se sale se recorta.
+    pub fn from_vec3(rgb: Vec3) -> Self {
+        Color {
+            r: (rgb.x * 255.0).round().clamp(0.0, 255.0) as u8,
+            g: (rgb.y * 255.0).round().clamp(0.0, 255.0) as u8,
+            b: (rgb.z * 255.0).round().clamp(0.0, 255.0) as u8,
+        }
+    }
+
 
 
     
@@ -1,25 +1,22 @@
+//! Los fragmentos que el rasterizador entrega a los fragment shaders.
 
 use nalgebra_glm::{Vec2, Vec3};
-use crate::color::Color;
 
+/// Un pixel cubierto por un triangulo, con los varyings del vertex shader
+/// interpolados, listo para el fragment shader. La iluminacion la calcula el
+/// shader (ver `shaders::iluminar`).
 pub struct Fragment {
+    /// Pixel en pantalla.
     pub position: Vec2,
-    pub color: Color,
+    /// Profundidad interpolada, la que se compara con el z-buffer.
     pub depth: f32,
+    /// Normal unitaria en espacio de mundo.
     pub normal: Vec3,
-    pub intensity: f32,
+    /// Posicion en espacio de mundo.
+    pub world_position: Vec3,
+    /// Posicion en espacio del objeto; los shaders muestrean el ruido aqui
+    /// para que el patron gire con el planeta.
     pub vertex_position: Vec3,
+    /// Coordenadas de textura interpoladas.
+    pub tex_coords: Vec2,
 }
-
-impl Fragment {
-    pub fn new(x: f32, y: f32, color: Color, depth: f32, normal: Vec3, intensity: f32, vertex_position: Vec3,) -> Self {
-        Fragment {
-            position: Vec2::new(x, y),
-            color,
-            depth,
-            normal,
-            intensity,
-            vertex_position
-        }
-    }
-}
\ No newline at end of file
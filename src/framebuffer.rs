@@ -1,14 +1,26 @@
+//! Buffers de color y profundidad, tiles y modos de mezcla.
 
+use std::io;
+use std::path::Path;
+use crate::image;
+
+/// Buffer de color (`0xRRGGBB`) y de profundidad de un cuadro.
 pub struct Framebuffer {
+    /// Ancho en pixeles.
     pub width: usize,
+    /// Alto en pixeles.
     pub height: usize,
+    /// Color de cada pixel como `0xRRGGBB`, fila por fila desde arriba.
     pub buffer: Vec<u32>,
+    /// Profundidad de cada pixel en el mismo orden que `buffer`; infinito donde
+    /// no hay geometria.
     pub zbuffer: Vec<f32>,
     background_color: u32,
     current_color: u32,
 }
 
 impl Framebuffer {
+    /// Framebuffer negro de `width` x `height` con el z-buffer vacio.
     pub fn new(width: usize, height: usize) -> Self {
         Framebuffer {
             width,
@@ -20,6 +32,7 @@ impl Framebuffer {
         }
     }
 
+    /// Llena el buffer con el color de fondo y vacia el z-buffer.
     pub fn clear(&mut self) {
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
@@ -29,6 +42,13 @@ impl Framebuffer {
         }
     }
 
+    /// Indica si un fragmento en (x, y) con esta profundidad quedaria visible.
+    pub fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        x < self.width && y < self.height && self.zbuffer[y * self.width + x] > depth
+    }
+
+    /// Pinta el pixel (x, y) con el color actual si `depth` esta mas cerca que lo
+    /// que ya hay, y guarda esa profundidad.
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
@@ -40,11 +60,187 @@ impl Framebuffer {
         }
     }
 
+    /// Color con el que `clear` llena el buffer.
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
 
+    /// Color que usa `point`.
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
-}
\ No newline at end of file
+
+    /// El buffer de color codificado como PPM.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        image::encode_ppm(self.width, self.height, &self.buffer)
+    }
+
+    /// El buffer de color codificado como BMP.
+    pub fn to_bmp(&self) -> Vec<u8> {
+        image::encode_bmp(self.width, self.height, &self.buffer)
+    }
+
+    /// El buffer de color codificado como PNG.
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        image::encode_png(self.width, self.height, &self.buffer)
+    }
+
+    /// Guarda el buffer de color como imagen; el formato sale de la extension
+    /// de `path` (`.png`, `.ppm` o `.bmp`).
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        image::save(path, self.width, self.height, &self.buffer)
+    }
+
+    /// El z-buffer como escala de grises: lo mas cercano en blanco, lo mas
+    /// lejano en negro y los pixeles sin geometria tambien en negro. El rango
+    /// se normaliza a las profundidades presentes en el cuadro.
+    pub fn depth_image(&self) -> Vec<u32> {
+        let (min, max) = self
+            .zbuffer
+            .iter()
+            .filter(|depth| depth.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &depth| (min.min(depth), max.max(depth)));
+        let range = if max > min { max - min } else { 1.0 };
+
+        self.zbuffer
+            .iter()
+            .map(|&depth| {
+                if !depth.is_finite() {
+                    return 0x000000;
+                }
+                let gray = (255.0 * (1.0 - (depth - min) / range)).round() as u32;
+                (gray << 16) | (gray << 8) | gray
+            })
+            .collect()
+    }
+
+    /// Guarda `depth_image`; el formato sale de la extension de `path`.
+    pub fn save_depth(&self, path: &Path) -> io::Result<()> {
+        image::save(path, self.width, self.height, &self.depth_image())
+    }
+
+    /// Copia una region del framebuffer (color y profundidad) a un tile.
+    pub fn read_tile(&self, x: usize, y: usize, width: usize, height: usize) -> Tile {
+        let mut buffer = Vec::with_capacity(width * height);
+        let mut zbuffer = Vec::with_capacity(width * height);
+        for row in y..y + height {
+            let start = row * self.width + x;
+            buffer.extend_from_slice(&self.buffer[start..start + width]);
+            zbuffer.extend_from_slice(&self.zbuffer[start..start + width]);
+        }
+
+        Tile { x, y, width, height, buffer, zbuffer }
+    }
+
+    /// Escribe de vuelta un tile leido con `read_tile`.
+    pub fn write_tile(&mut self, tile: &Tile) {
+        for row in 0..tile.height {
+            let start = (tile.y + row) * self.width + tile.x;
+            let local = row * tile.width;
+            self.buffer[start..start + tile.width].copy_from_slice(&tile.buffer[local..local + tile.width]);
+            self.zbuffer[start..start + tile.width].copy_from_slice(&tile.zbuffer[local..local + tile.width]);
+        }
+    }
+}
+
+/// Una region rectangular del framebuffer con su propia copia de color y
+/// profundidad, para que varios hilos puedan rasterizar sin compartir memoria.
+pub struct Tile {
+    /// Columna de la esquina superior izquierda en el framebuffer.
+    pub x: usize,
+    /// Fila de la esquina superior izquierda en el framebuffer.
+    pub y: usize,
+    /// Ancho en pixeles.
+    pub width: usize,
+    /// Alto en pixeles.
+    pub height: usize,
+    /// Color, fila por fila.
+    pub buffer: Vec<u32>,
+    /// Profundidad, fila por fila.
+    pub zbuffer: Vec<f32>,
+}
+
+/// Como se combina el color de un fragmento con el que ya esta en el pixel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Reemplaza el pixel.
+    #[default]
+    Opaque,
+    /// Mezcla con la transparencia del fragmento: `src * a + dst * (1 - a)`.
+    Alpha,
+    /// Suma `src * a` al pixel, saturando en blanco. Sirve para brillos.
+    Additive,
+}
+
+impl BlendMode {
+    /// Combina `src` (`0xRRGGBB`) con opacidad `alpha` sobre `dst`.
+    pub fn apply(self, dst: u32, src: u32, alpha: f32) -> u32 {
+        let canal = |color: u32, shift: u32| ((color >> shift) & 0xFF) as f32;
+        let mezcla = |shift: u32| -> u32 {
+            let (d, s) = (canal(dst, shift), canal(src, shift));
+            let valor = match self {
+                BlendMode::Opaque => s,
+                BlendMode::Alpha => s * alpha + d * (1.0 - alpha),
+                BlendMode::Additive => d + s * alpha,
+            };
+            (valor.round().clamp(0.0, 255.0) as u32) << shift
+        };
+
+        match self {
+            BlendMode::Opaque => src,
+            _ => mezcla(16) | mezcla(8) | mezcla(0),
+        }
+    }
+}
+
+/// Destino de la rasterizacion: el framebuffer completo o un tile.
+/// Las coordenadas siempre son las del framebuffer.
+pub trait RenderTarget {
+    /// Rectangulo cubierto como (min_x, min_y, max_x, max_y), con max exclusivo.
+    fn bounds(&self) -> (usize, usize, usize, usize);
+    /// Indica si un fragmento en (x, y) con esta profundidad quedaria visible.
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool;
+    /// Color y profundidad del pixel (x, y).
+    fn pixel_mut(&mut self, x: usize, y: usize) -> (&mut u32, &mut f32);
+
+    /// Escribe un fragmento que ya paso la prueba de profundidad. Con `depth`
+    /// en `None` el z-buffer no cambia, como en las capas transparentes que se
+    /// dibujan despues de la geometria opaca.
+    fn write(&mut self, x: usize, y: usize, color: u32, alpha: f32, blend: BlendMode, depth: Option<f32>) {
+        let (pixel, z) = self.pixel_mut(x, y);
+        *pixel = blend.apply(*pixel, color, alpha);
+        if let Some(depth) = depth {
+            *z = depth;
+        }
+    }
+}
+
+impl RenderTarget for Framebuffer {
+    fn bounds(&self) -> (usize, usize, usize, usize) {
+        (0, 0, self.width, self.height)
+    }
+
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        Framebuffer::depth_test(self, x, y, depth)
+    }
+
+    fn pixel_mut(&mut self, x: usize, y: usize) -> (&mut u32, &mut f32) {
+        let index = y * self.width + x;
+        (&mut self.buffer[index], &mut self.zbuffer[index])
+    }
+}
+
+impl RenderTarget for Tile {
+    fn bounds(&self) -> (usize, usize, usize, usize) {
+        (self.x, self.y, self.x + self.width, self.y + self.height)
+    }
+
+    fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        self.zbuffer[(y - self.y) * self.width + (x - self.x)] > depth
+    }
+
+    fn pixel_mut(&mut self, x: usize, y: usize) -> (&mut u32, &mut f32) {
+        let index = (y - self.y) * self.width + (x - self.x);
+        (&mut self.buffer[index], &mut self.zbuffer[index])
+    }
+}
@@ -0,0 +1,135 @@
+//! Codificacion de buffers de color `0xRRGGBB` como PNG, PPM o BMP.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Formatos en los que se puede guardar un buffer de color `0xRRGGBB`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// PPM binario (P6).
+    Ppm,
+    /// PNG RGB de 8 bits.
+    Png,
+    /// BMP de 24 bits sin compresion.
+    Bmp,
+}
+
+impl ImageFormat {
+    /// El formato de una extension como `png`, sin distinguir mayusculas.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "bmp" => Some(ImageFormat::Bmp),
+            _ => None,
+        }
+    }
+
+    /// El formato que corresponde a la extension de `path`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(ImageFormat::from_extension)
+    }
+
+    /// La extension de archivo, sin el punto.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Bmp => "bmp",
+        }
+    }
+}
+
+/// Codifica pixeles `0xRRGGBB` como PPM binario (P6).
+pub fn encode_ppm(width: usize, height: usize, pixels: &[u32]) -> Vec<u8> {
+    let mut data = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    data.reserve(width * height * 3);
+    data.extend(pixels.iter().flat_map(|&pixel| rgb(pixel)));
+    data
+}
+
+/// Codifica pixeles `0xRRGGBB` como PNG RGB de 8 bits.
+pub fn encode_png(width: usize, height: usize, pixels: &[u32]) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let rgb_data: Vec<u8> = pixels.iter().flat_map(|&pixel| rgb(pixel)).collect();
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&rgb_data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+
+    Ok(data)
+}
+
+/// Codifica pixeles `0xRRGGBB` como BMP de 24 bits sin compresion.
+pub fn encode_bmp(width: usize, height: usize, pixels: &[u32]) -> Vec<u8> {
+    // Cada fila se guarda de abajo hacia arriba, en BGR y rellenada a 4 bytes.
+    let row_size = (width * 3).div_ceil(4) * 4;
+    let image_size = row_size * height;
+    let file_size = 14 + 40 + image_size;
+
+    let mut data = Vec::with_capacity(file_size);
+    data.extend_from_slice(b"BM");
+    data.extend_from_slice(&(file_size as u32).to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&(14u32 + 40).to_le_bytes());
+
+    data.extend_from_slice(&40u32.to_le_bytes());
+    data.extend_from_slice(&(width as i32).to_le_bytes());
+    data.extend_from_slice(&(height as i32).to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&24u16.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&(image_size as u32).to_le_bytes());
+    data.extend_from_slice(&2835i32.to_le_bytes());
+    data.extend_from_slice(&2835i32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+
+    for row in pixels.chunks(width).rev() {
+        for &pixel in row {
+            let [r, g, b] = rgb(pixel);
+            data.extend_from_slice(&[b, g, r]);
+        }
+        data.resize(data.len() + row_size - width * 3, 0);
+    }
+
+    data
+}
+
+/// El formato que corresponde a la extension de `path`.
+pub fn format_for(path: &Path) -> io::Result<ImageFormat> {
+    ImageFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("formato de imagen no soportado: {}", path.display()),
+        )
+    })
+}
+
+/// Guarda pixeles `0xRRGGBB` en `path`, eligiendo el formato por la extension.
+pub fn save(path: &Path, width: usize, height: usize, pixels: &[u32]) -> io::Result<()> {
+    let data = match format_for(path)? {
+        ImageFormat::Ppm => encode_ppm(width, height, pixels),
+        ImageFormat::Png => encode_png(width, height, pixels)?,
+        ImageFormat::Bmp => encode_bmp(width, height, pixels),
+    };
+
+    write_file(path, &data)
+}
+
+/// Escribe `data` en `path`, reemplazando el archivo si ya existe.
+pub fn write_file(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_all(data)?;
+    file.flush()
+}
+
+fn rgb(pixel: u32) -> [u8; 3] {
+    [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]
+}
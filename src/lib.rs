@@ -0,0 +1,47 @@
+//! Rasterizador por software para renderizar planetas procedurales.
+//!
+//! El flujo de un cuadro es:
+//!
+//! 1. Cargar una malla con [`obj::Obj`] y obtener sus vertices con
+//!    [`obj::Obj::get_vertex_array`].
+//! 2. Armar las [`pipeline::Uniforms`] con las matrices de
+//!    [`pipeline::create_model_matrix`], [`pipeline::create_view_matrix`],
+//!    [`pipeline::create_perspective_matrix`] y
+//!    [`pipeline::create_viewport_matrix`] (la vista suele salir de una
+//!    [`camera::Camera`]) y una [`noise_source::NoiseSource`] de [`ruido`]
+//!    (los presets se describen en TOML, ver [`preset::NoisePreset`]).
+//! 3. Dibujar con [`pipeline::render_shader`] sobre un
+//!    [`framebuffer::Framebuffer`] usando alguno de los fragment shaders de
+//!    [`shaders`]. [`registry::planetas`] reune los planetas de la demo con su
+//!    ruido por nombre.
+//!    Para varios objetos con z-buffer compartido se arma una
+//!    [`scene::Scene`], a mano o desde un archivo TOML con
+//!    [`scene_file::load`], y se dibuja con [`pipeline::render_scene`].
+//! 4. Mostrar `framebuffer.buffer` en una ventana o guardarlo con
+//!    [`framebuffer::Framebuffer::save`].
+
+#![warn(missing_docs)]
+
+pub mod camera;
+pub mod clipping;
+pub mod color;
+pub mod fragment;
+pub mod framebuffer;
+pub mod image;
+pub mod lighting;
+pub mod mesh;
+pub mod noise_source;
+pub mod obj;
+pub mod pipeline;
+pub mod preset;
+pub mod registry;
+pub mod ruido;
+pub mod scene;
+pub mod scene_file;
+pub mod shaders;
+pub mod tiles;
+pub mod toml_file;
+pub mod triangle;
+pub mod vertex;
+
+pub use pipeline::Uniforms;
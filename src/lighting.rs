@@ -0,0 +1,195 @@
+//! Luces, materiales y modelos especulares con los que se iluminan los
+//! fragmentos.
+
+use nalgebra_glm::{dot, Vec3};
+use crate::color::Color;
+
+/// Tipo de luz. Las posiciones y direcciones estan en espacio de mundo.
+#[derive(Clone, Copy, Debug)]
+pub enum LightKind {
+    /// Luz muy lejana (el sol visto desde un planeta): todos los rayos van en
+    /// la misma `direction`, hacia donde apunta la luz.
+    Directional {
+        /// Direccion unitaria en la que viajan los rayos.
+        direction: Vec3,
+    },
+    /// Luz que sale de `position` y se atenua con la distancia: a distancia
+    /// `range` llega con la mitad de su intensidad.
+    Point {
+        /// Posicion de la luz.
+        position: Vec3,
+        /// Distancia a la que llega con la mitad de su intensidad.
+        range: f32,
+    },
+}
+
+/// Una luz de la escena.
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    /// Direccional o puntual, con su direccion o posicion.
+    pub kind: LightKind,
+    /// Color de la luz.
+    pub color: Color,
+    /// Multiplica el color; 1 es la intensidad normal.
+    pub intensity: f32,
+}
+
+impl Light {
+    /// Luz direccional; `direction` se normaliza.
+    pub fn directional(direction: Vec3, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Directional { direction: direction.normalize() },
+            color,
+            intensity,
+        }
+    }
+
+    /// Luz puntual en `position` que llega con la mitad de su intensidad a
+    /// distancia `range`.
+    pub fn point(position: Vec3, range: f32, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Point { position, range },
+            color,
+            intensity,
+        }
+    }
+
+    /// Direccion unitaria desde `position` hacia la luz y cuanto llega de ella
+    /// (1 para luces direccionales).
+    fn incidence(&self, position: &Vec3) -> (Vec3, f32) {
+        match self.kind {
+            LightKind::Directional { direction } => (-direction, 1.0),
+            LightKind::Point { position: light_position, range } => {
+                let to_light = light_position - position;
+                let distance = to_light.magnitude();
+                if distance == 0.0 {
+                    return (Vec3::zeros(), 1.0);
+                }
+                let d = distance / range;
+                (to_light / distance, 1.0 / (1.0 + d * d))
+            }
+        }
+    }
+}
+
+/// Como se calcula el brillo especular.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecularModel {
+    /// Reflejo del rayo de luz contra la direccion de vista.
+    Phong,
+    /// Vector medio entre la luz y la vista contra la normal; el brillo es un
+    /// poco mas ancho y no se corta de golpe en angulos rasantes.
+    BlinnPhong,
+}
+
+impl SpecularModel {
+    /// El otro modelo.
+    pub fn toggle(self) -> Self {
+        match self {
+            SpecularModel::Phong => SpecularModel::BlinnPhong,
+            SpecularModel::BlinnPhong => SpecularModel::Phong,
+        }
+    }
+}
+
+/// Como responde una superficie a la luz. El valor por defecto es difuso puro
+/// sin ambiente ni brillo (iluminacion de Lambert).
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    /// Cuanto refleja de `Lighting::ambient`.
+    pub ambient: f32,
+    /// Peso del termino difuso de Lambert.
+    pub diffuse: f32,
+    /// Peso del brillo especular.
+    pub specular: f32,
+    /// Exponente especular: valores altos dan brillos mas chicos y definidos.
+    pub shininess: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            ambient: 0.0,
+            diffuse: 1.0,
+            specular: 0.0,
+            shininess: 32.0,
+        }
+    }
+}
+
+/// Las luces de la escena, compartidas por todos los shaders a traves de las
+/// `Uniforms`.
+#[derive(Clone, Debug)]
+pub struct Lighting {
+    /// Las luces; sus aportes se suman.
+    pub lights: Vec<Light>,
+    /// Luz ambiente, multiplicada por `Material::ambient`.
+    pub ambient: Color,
+    /// Modelo especular que usan todos los materiales.
+    pub model: SpecularModel,
+}
+
+impl Default for Lighting {
+    /// Una luz blanca que viene de +z, de frente a la camara inicial.
+    fn default() -> Self {
+        Lighting {
+            lights: vec![Light::directional(Vec3::new(0.0, 0.0, -1.0), Color::new(255, 255, 255), 1.0)],
+            ambient: Color::new(255, 255, 255),
+            model: SpecularModel::BlinnPhong,
+        }
+    }
+}
+
+impl Lighting {
+    /// Un sol en `position` que ilumina igual a cualquier distancia, con un
+    /// poco de luz ambiente para que el lado nocturno no quede negro puro.
+    pub fn sun(position: Vec3) -> Self {
+        Lighting {
+            lights: vec![Light::point(position, f32::INFINITY, Color::new(255, 250, 240), 1.0)],
+            ambient: Color::new(40, 45, 60),
+            model: SpecularModel::BlinnPhong,
+        }
+    }
+
+    /// Ilumina `base_color` en un punto de la superficie. `position` y `normal`
+    /// estan en espacio de mundo y `eye` es la posicion de la camara.
+    ///
+    /// La parte ambiente y difusa multiplica el color de la superficie; la
+    /// especular se suma con el color de la luz.
+    pub fn shade(&self, base_color: Color, material: &Material, position: &Vec3, normal: &Vec3, eye: &Vec3) -> Color {
+        let normal = normal.normalize();
+        let view = (eye - position).normalize();
+
+        let mut diffuse = self.ambient.to_vec3() * material.ambient;
+        let mut specular = Vec3::zeros();
+
+        for light in &self.lights {
+            let (to_light, attenuation) = light.incidence(position);
+            let n_dot_l = dot(&normal, &to_light);
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            let radiance = light.color.to_vec3() * (light.intensity * attenuation);
+            diffuse += radiance * (material.diffuse * n_dot_l);
+
+            if material.specular > 0.0 {
+                let alignment = match self.model {
+                    SpecularModel::Phong => {
+                        let reflected = normal * (2.0 * n_dot_l) - to_light;
+                        dot(&reflected, &view)
+                    }
+                    SpecularModel::BlinnPhong => dot(&normal, &(to_light + view).normalize()),
+                };
+                specular += radiance * (material.specular * alignment.max(0.0).powf(material.shininess));
+            }
+        }
+
+        let lit = base_color.scale_rgb(diffuse);
+        if specular == Vec3::zeros() {
+            lit
+        } else {
+            Color::from_vec3(lit.to_vec3() + specular)
+        }
+    }
+}
@@ -1,159 +1,395 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
-use std::time::Duration;
+use nalgebra_glm::Vec3;
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
 use std::f32::consts::PI;
 
-mod framebuffer;
-mod triangle;
-mod vertex;
-mod obj;
-mod color;
-mod fragment;
-mod shaders;
-mod camera;
-
-use framebuffer::Framebuffer;
-use vertex::Vertex;
-use obj::Obj;
-use camera::Camera;
-use triangle::triangle;
-use shaders::{aurora_shader, crateres_shader, desierto_shader, hielo_shader, jungla_shader, metano_shader, oceano_profundo_shader, rocoso_montanoso_shader, shader_agua, volcanico_shader};
-use crate::fragment::Fragment;
-use crate::color::Color;
-use crate::shaders::vertex_shader;
-use noise::Simplex;
-use fastnoise_lite::FastNoiseLite;
- 
-pub struct Uniforms {
-    model_matrix: Mat4,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    viewport_matrix: Mat4,
-    time: u32,
-    noise: FastNoiseLite
+use lab4::camera::Camera;
+use lab4::framebuffer::Framebuffer;
+use lab4::image::ImageFormat;
+use lab4::obj::Obj;
+use lab4::pipeline::{render_scene, FrameContext};
+use lab4::registry::{planetas, ShaderEntry, ShaderRegistry};
+use lab4::ruido::{presets_with_seed, NoiseLibrary};
+use lab4::scene::{Scene, SceneError, SceneObject, Transform};
+use lab4::scene_file::{self, FileWatcher};
+use lab4::triangle::{CullMode, FrontFace, RasterState};
+use lab4::vertex::Vertex;
+
+/// Escena que se muestra si no se pide otra ni un planeta suelto.
+const ESCENA: &str = "assets/escenas/sistema_solar.toml";
+
+/// Malla del planeta de `--shader` si no se indica otra con `--mesh`.
+const MALLA: &str = "assets/sphere.obj";
+
+/// Posicion del sol cuando se muestra un solo planeta: arriba a la derecha y
+/// un poco detras, para que se vea el terminador y parte del lado nocturno.
+const SOL: Vec3 = Vec3::new(30.0, 8.0, 10.0);
+
+const USO: &str = "\
+uso: lab4 [opciones]
+
+Sin --scene ni --shader se muestra assets/escenas/sistema_solar.toml. La
+escena se vuelve a cargar cada vez que cambia el archivo.
+
+  --scene ARCHIVO       escena TOML a mostrar
+  --shader NOMBRE       planeta suelto con el que empieza el visor en lugar de
+                        una escena (ver --list-shaders)
+  --mesh ARCHIVO        malla .obj del planeta de --shader [assets/sphere.obj]
+  --noise PRESET        preset de ruido para el planeta de --shader
+  --noise-dir DIR       carga presets de ruido *.toml extra de DIR
+  --seed N              semilla para todos los presets de ruido incluidos
+  --list-shaders        lista los shaders y termina
+  --window ANCHOxALTO   tamano de la ventana [1000x800]
+  --resolution ANCHOxALTO
+                        resolucion del framebuffer [la de la ventana]
+  --fps N               cuadros por segundo del visor, 0 sin limite [60]
+  --headless            renderiza sin ventana y guarda las imagenes
+  --frames N            cuadros a guardar con --headless [1]
+  --output DIR          directorio de salida de --headless [output]
+  --format FORMATO      formato de --headless: png, ppm o bmp [png]
+  --depth               con --headless, guarda tambien el z-buffer
+  --help                muestra esta ayuda
+
+Teclas del visor:
+  flechas, WASD, Q/E    mueven la camara
+  1-0, N, B             cambian de planeta (con --shader)
+  Tab                   elige el objeto de la escena que cambia M
+  M                     pasa al siguiente preset de ruido del objeto
+  C, V, T, L            cull, orden de vertices, tiles y modelo especular
+  P                     guarda una captura";
+
+/// Opciones de linea de comandos; ver `USO`.
+struct Options {
+    help: bool,
+    headless: bool,
+    list_shaders: bool,
+    /// Archivo de escena (ver `scene_file::SceneDescription`).
+    scene: Option<PathBuf>,
+    /// Planeta del registro con el que empieza el visor en lugar de una
+    /// escena; despues se cambia con N y B.
+    shader: Option<String>,
+    /// Malla del planeta de `--shader`.
+    mesh: Option<PathBuf>,
+    /// Preset de ruido que reemplaza al registrado para el shader de `--shader`.
+    noise: Option<String>,
+    /// Directorio con presets de ruido `*.toml` extra.
+    noise_dir: Option<PathBuf>,
+    seed: Option<u32>,
+    window: (usize, usize),
+    /// `None` usa el tamano de la ventana.
+    resolution: Option<(usize, usize)>,
+    fps: usize,
+    frames: u32,
+    output: PathBuf,
+    format: ImageFormat,
+    depth: bool,
 }
 
-pub struct UniformsSimplex {
-    model_matrix: Mat4,
-    view_matrix: Mat4,
-    projection_matrix: Mat4,
-    viewport_matrix: Mat4,
-    time: u32,
-    noise: Simplex,
+/// Lee un tamano como `1000x800`.
+fn parse_size(name: &str, value: &str) -> Result<(usize, usize), String> {
+    let error = || format!("{} debe ser ANCHOxALTO con enteros positivos, como 1000x800, no {}", name, value);
+    let (ancho, alto) = value.split_once('x').ok_or_else(error)?;
+    match (ancho.parse(), alto.parse()) {
+        (Ok(ancho), Ok(alto)) if ancho > 0 && alto > 0 => Ok((ancho, alto)),
+        _ => Err(error()),
+    }
 }
 
- 
-
-fn crear_ruido_perlin() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        help: false,
+        headless: false,
+        list_shaders: false,
+        scene: None,
+        shader: None,
+        mesh: None,
+        noise: None,
+        noise_dir: None,
+        seed: None,
+        window: (1000, 800),
+        resolution: None,
+        fps: 60,
+        frames: 1,
+        output: PathBuf::from("output"),
+        format: ImageFormat::Png,
+        depth: false,
+    };
+
+    // La primera opcion dada que solo tiene sentido con --headless.
+    let mut solo_headless = None;
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("falta el valor de {}", name));
+        if solo_headless.is_none() && matches!(arg.as_str(), "--frames" | "--output" | "--format" | "--depth") {
+            solo_headless = Some(arg.clone());
+        }
+        match arg.as_str() {
+            "--help" | "-h" => options.help = true,
+            "--headless" => options.headless = true,
+            "--list-shaders" => options.list_shaders = true,
+            "--depth" => options.depth = true,
+            "--frames" => {
+                options.frames = value("--frames")?
+                    .parse()
+                    .ok()
+                    .filter(|&frames| frames > 0)
+                    .ok_or_else(|| "--frames debe ser un entero positivo".to_string())?;
+            }
+            "--fps" => {
+                options.fps = value("--fps")?
+                    .parse()
+                    .map_err(|_| "--fps debe ser un entero (0 sin limite)".to_string())?;
+            }
+            "--seed" => {
+                options.seed = Some(
+                    value("--seed")?
+                        .parse()
+                        .map_err(|_| "--seed debe ser un entero entre 0 y 4294967295".to_string())?,
+                );
+            }
+            "--window" => options.window = parse_size("--window", &value("--window")?)?,
+            "--resolution" => options.resolution = Some(parse_size("--resolution", &value("--resolution")?)?),
+            "--mesh" => options.mesh = Some(PathBuf::from(value("--mesh")?)),
+            "--output" => options.output = PathBuf::from(value("--output")?),
+            "--format" => {
+                let format = value("--format")?;
+                options.format = ImageFormat::from_extension(&format)
+                    .ok_or_else(|| format!("formato no soportado: {} (usar png, ppm o bmp)", format))?;
+            }
+            "--scene" => options.scene = Some(PathBuf::from(value("--scene")?)),
+            "--shader" => options.shader = Some(value("--shader")?),
+            "--noise" => options.noise = Some(value("--noise")?),
+            "--noise-dir" => options.noise_dir = Some(PathBuf::from(value("--noise-dir")?)),
+            _ => return Err(format!("argumento desconocido: {}", arg)),
+        }
+    }
 
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Perlin));
+    if let Some(opcion) = solo_headless.filter(|_| !options.headless) {
+        return Err(format!("{} solo se usa con --headless", opcion));
+    }
+    if options.scene.is_some() && options.shader.is_some() {
+        return Err("--scene y --shader no se pueden usar juntos".to_string());
+    }
+    if options.shader.is_none() {
+        if options.mesh.is_some() {
+            return Err("--mesh solo se usa con --shader; las mallas de una escena van en su archivo".to_string());
+        }
+        if options.noise.is_some() {
+            return Err("--noise solo se usa con --shader; el ruido de una escena va en su archivo".to_string());
+        }
+    }
 
-    noise.set_seed(Some(100)); 
-    noise.set_frequency(Some(0.030)); 
+    Ok(options)
+}
 
-    noise.set_fractal_type(Some(fastnoise_lite::FractalType::PingPong));
-    noise.set_fractal_octaves(Some(9)); 
-    noise.set_fractal_lacunarity(Some(1.0)); 
-    noise.set_fractal_gain(Some(0.100)); 
-    noise.set_fractal_ping_pong_strength(Some(9.0)); 
-    noise
+/// Revisa lo que `parse_args` no puede saber solo con los argumentos: que el
+/// shader y el ruido existan y que la malla sea un archivo.
+fn validar(options: &Options, registry: &ShaderRegistry, noise_library: &NoiseLibrary) -> Result<(), String> {
+    if let Some(noise) = &options.noise {
+        if !noise_library.contains(noise) {
+            let disponibles: Vec<&str> = noise_library.names().collect();
+            return Err(format!("ruido desconocido: {} (disponibles: {})", noise, disponibles.join(", ")));
+        }
+    }
+    if let Some(shader) = &options.shader {
+        if registry.get(shader).is_none() {
+            return Err(format!("shader desconocido: {} (ver --list-shaders)", shader));
+        }
+        let mesh = options.mesh.as_deref().unwrap_or(Path::new(MALLA));
+        if !mesh.is_file() {
+            return Err(format!("no existe la malla {}", mesh.display()));
+        }
+    }
+    Ok(())
 }
 
- 
-
-fn crear_ruido_cellular_bacteria() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Cellular));
-    noise.set_seed(Some(1337)); 
-    noise.set_frequency(Some(0.010));  
-    noise.set_cellular_distance_function(Some(fastnoise_lite::CellularDistanceFunction::EuclideanSq));  
-    noise.set_cellular_return_type(Some(fastnoise_lite::CellularReturnType::Distance2Mul));  
-    noise.set_cellular_jitter(Some(1.0)); 
-    noise.set_fractal_type(Some(fastnoise_lite::FractalType::PingPong));  
-    noise.set_fractal_octaves(Some(3));  
-    noise.set_fractal_lacunarity(Some(2.0));  
-    noise.set_fractal_gain(Some(1.0)); 
-    noise.set_fractal_ping_pong_strength(Some(7.0)); 
-    noise 
+/// Renderiza `options.frames` cuadros sin abrir ventana y los guarda como
+/// `frame_0000.png`, `frame_0001.png`, ... en `options.output`. Con `--depth`
+/// tambien guarda el z-buffer como `depth_0000.png`, ...
+fn run_headless(
+    options: &Options,
+    scene: &Scene,
+    registry: &ShaderRegistry,
+    noise_library: &NoiseLibrary,
+) -> std::io::Result<()> {
+    let (width, height) = options.resolution.unwrap_or(options.window);
+    let mut framebuffer = Framebuffer::new(width, height);
+
+    let raster_state = RasterState {
+        cull_mode: CullMode::Back,
+        front_face: FrontFace::CounterClockwise,
+        tiled: true,
+        ..RasterState::default()
+    };
+
+    fs::create_dir_all(&options.output)?;
+
+    for frame in 0..options.frames {
+        let frame_context = FrameContext {
+            camera: &scene.camera,
+            lighting: &scene.lighting,
+            sun_position: scene.sun_position,
+            time: frame + 1,
+        };
+        render_scene(&mut framebuffer, &frame_context, scene, registry, noise_library, &raster_state);
+
+        let path = options
+            .output
+            .join(format!("frame_{:04}.{}", frame, options.format.extension()));
+        framebuffer.save(&path)?;
+        println!("{}", path.display());
+
+        if options.depth {
+            let path = options
+                .output
+                .join(format!("depth_{:04}.{}", frame, options.format.extension()));
+            framebuffer.save_depth(&path)?;
+            println!("{}", path.display());
+        }
+    }
+
+    Ok(())
 }
 
-fn crear_ruido_cellular_agujero_negro() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Perlin));
-    noise.set_seed(Some(100)); 
-    noise.set_frequency(Some(0.030));  
-    noise.set_fractal_type(Some(fastnoise_lite::FractalType::PingPong));  
-    noise.set_fractal_octaves(Some(9));  
-    noise.set_fractal_lacunarity(Some(1.0));  
-    noise.set_fractal_gain(Some(1.0)); 
-    noise.set_fractal_weighted_strength(Some(3.0));
-    noise.set_fractal_ping_pong_strength(Some(10.0));  
-    noise 
+/// Escena con un solo planeta del registro en el origen, con su transformacion
+/// por defecto, frente a la camara e iluminado por `SOL`.
+fn un_planeta(esfera: Vec<Vertex>, entry: &ShaderEntry, noise: Option<&str>) -> Scene {
+    let camera = Camera::new(
+        Vec3::new(0.0, 0.0, 5.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
+    );
+    let mut scene = Scene::new(camera, 0x009965, SOL);
+    scene.insert_mesh("esfera", esfera);
+
+    let transform = Transform {
+        rotation: entry.defaults.rotation,
+        scale: entry.defaults.scale,
+        ..Transform::default()
+    };
+    let mut planeta = SceneObject::new(&entry.name, "esfera", &entry.name, transform);
+    planeta.noise = noise.map(str::to_string);
+    scene.add(planeta);
+    scene
 }
 
-fn crear_ruido_camo() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::OpenSimplex2));
-    noise.set_seed(Some(1337)); 
-    noise.set_frequency(Some(0.010));  
-    noise.set_fractal_type(Some(fastnoise_lite::FractalType::Ridged));  
-    noise.set_fractal_octaves(Some(9));  
-    noise.set_fractal_lacunarity(Some(5.0));  
-    noise.set_fractal_gain(Some(1.0)); 
-    noise.set_fractal_weighted_strength(Some(7.0)); 
-    noise 
+/// Cambia el planeta de `un_planeta` por el de `entry`, con su transformacion
+/// por defecto. El ruido elegido con M se conserva.
+fn cambiar_planeta(planeta: &mut SceneObject, entry: &ShaderEntry) {
+    planeta.name = entry.name.clone();
+    planeta.shader = entry.name.clone();
+    planeta.transform.rotation = entry.defaults.rotation;
+    planeta.transform.scale = entry.defaults.scale;
 }
 
-fn crear_ruido_variado() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Cellular));
-    noise.set_seed(Some(100)); 
-    noise.set_frequency(Some(0.030));  
-    noise.set_fractal_type(Some(fastnoise_lite::FractalType::FBm));  
-    noise.set_fractal_octaves(Some(9));  
-    noise.set_fractal_lacunarity(Some(1.0));  
-    noise.set_fractal_gain(Some(1.0)); 
-    noise.set_fractal_weighted_strength(Some(3.0)); 
-    noise.set_cellular_distance_function(Some(fastnoise_lite::CellularDistanceFunction::EuclideanSq));  
-    noise.set_cellular_return_type(Some(fastnoise_lite::CellularReturnType::Distance2Div));  
-    noise.set_cellular_jitter(Some(1.0)); 
-    noise 
+/// El ruido que sigue a `actual` en `nombres`. Despues del ultimo vuelve a
+/// `None`, el registrado para el shader.
+fn ruido_siguiente(actual: Option<&str>, nombres: &[&str]) -> Option<String> {
+    let siguiente = match actual.and_then(|name| nombres.iter().position(|n| *n == name)) {
+        None => 0,
+        Some(index) => index + 1,
+    };
+    nombres.get(siguiente).map(|name| name.to_string())
 }
 
-fn crear_ruido_grupos() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Cellular));
-    noise.set_seed(Some(1337)); 
-    noise.set_frequency(Some(0.030));  
-    noise.set_cellular_distance_function(Some(fastnoise_lite::CellularDistanceFunction::Hybrid));  
-    noise.set_cellular_return_type(Some(fastnoise_lite::CellularReturnType::Distance2Sub));  
-    noise.set_cellular_jitter(Some(2.0)); 
-    noise.set_fractal_type(Some(fastnoise_lite::FractalType::PingPong));  
-    noise.set_fractal_octaves(Some(3));  
-    noise.set_fractal_lacunarity(Some(2.0));  
-    noise.set_fractal_gain(Some(0.5)); 
-    noise.set_fractal_ping_pong_strength(Some(1.0)); 
-    noise 
+/// Titulo con el objeto seleccionado y su ruido; en una escena tambien el
+/// archivo.
+fn titulo_ventana(scene: &Scene, seleccionado: usize, registry: &ShaderRegistry, archivo: Option<&Path>) -> String {
+    let mut titulo = "LAB 4".to_string();
+    if let Some(path) = archivo {
+        titulo += &format!(" - {}", path.display());
+    }
+    if let Some(object) = scene.objects.get(seleccionado) {
+        let noise = object
+            .noise
+            .as_deref()
+            .or_else(|| registry.get(&object.shader).map(|entry| entry.noise.as_str()))
+            .unwrap_or("?");
+        titulo += &format!(" - {} ({})", object.name, noise);
+    }
+    titulo
 }
 
-fn crear_ruido_cellular_puntas() -> FastNoiseLite {
-    let mut noise = FastNoiseLite::new();
-    noise.set_noise_type(Some(fastnoise_lite::NoiseType::Cellular));
-    noise.set_seed(Some(1337)); 
-    noise.set_frequency(Some(0.030));  
-    noise.set_cellular_distance_function(Some(fastnoise_lite::CellularDistanceFunction::Manhattan));  
-    noise.set_cellular_return_type(Some(fastnoise_lite::CellularReturnType::Distance));  
-    noise.set_cellular_jitter(Some(1.0)); 
-    noise 
+/// Carga una escena y revisa que sus shaders y ruidos existan.
+fn cargar_escena(path: &Path, registry: &ShaderRegistry, noise_library: &NoiseLibrary) -> Result<Scene, SceneError> {
+    let scene = scene_file::load(path)?;
+    scene
+        .validate(registry, noise_library)
+        .map_err(|error| SceneError::Archivo { path: path.to_path_buf(), error: Box::new(error) })?;
+    Ok(scene)
 }
 
 fn main() {
-    let window_width = 1000;
-    let window_height = 800;
-    let framebuffer_width = 1000;
-    let framebuffer_height = 800;
-    let frame_delay = Duration::from_millis(16);
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {} (ver --help)", error);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USO);
+        return;
+    }
+
+    let registry = planetas();
+    let mut noise_library = presets_with_seed(options.seed);
+    if let Some(dir) = &options.noise_dir {
+        if let Err(error) = noise_library.load_dir(dir) {
+            eprintln!("error: {}", error);
+            process::exit(2);
+        }
+    }
+    if options.list_shaders {
+        for name in registry.names() {
+            println!("{}", name);
+        }
+        return;
+    }
+
+    if let Err(error) = validar(&options, &registry, &noise_library) {
+        eprintln!("error: {}", error);
+        process::exit(2);
+    }
+
+    let mut watcher = None;
+    let mut scene = match options.shader.as_deref().and_then(|shader| registry.get(shader)) {
+        Some(entry) => {
+            let mesh = options.mesh.clone().unwrap_or_else(|| PathBuf::from(MALLA));
+            let obj = Obj::load(&mesh.to_string_lossy()).unwrap_or_else(|error| {
+                eprintln!("error: no se pudo cargar la malla {}: {}", mesh.display(), error);
+                process::exit(2);
+            });
+            let scene = un_planeta(obj.get_vertex_array(), entry, options.noise.as_deref());
+            if let Err(error) = scene.validate(&registry, &noise_library) {
+                eprintln!("error: {}", error);
+                process::exit(2);
+            }
+            scene
+        }
+        None => {
+            let path = options.scene.clone().unwrap_or_else(|| PathBuf::from(ESCENA));
+            let scene = cargar_escena(&path, &registry, &noise_library).unwrap_or_else(|error| {
+                eprintln!("error: {}", error);
+                process::exit(2);
+            });
+            watcher = Some(FileWatcher::new(&path));
+            scene
+        }
+    };
+
+    if options.headless {
+        if let Err(error) = run_headless(&options, &scene, &registry, &noise_library) {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let (window_width, window_height) = options.window;
+    let (framebuffer_width, framebuffer_height) = options.resolution.unwrap_or(options.window);
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
     let mut window = Window::new(
@@ -162,220 +398,145 @@ fn main() {
         window_height,
         WindowOptions::default(),
     )
-    .unwrap();
+    .unwrap_or_else(|error| {
+        eprintln!("error: no se pudo abrir la ventana: {}", error);
+        process::exit(1);
+    });
 
+    window.set_target_fps(options.fps);
     window.set_position(500, 500);
     window.update();
 
-    framebuffer.set_background_color(0x009965);
-
-    let translation = Vec3::new(0.0, 0.0, 0.0);
-    let rotation = Vec3::new(0.0, 0.0, 0.0);
-    let rotation_anillos = Vec3::new(PI / 4.0, 0.0, 0.0);
-    let scale = 1.0f32;
-
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 5.0),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0)
-    );
-
-    let obj_sphere = Obj::load("assets/sphere.obj").expect("No se puede abrir el shpere.obj");
-    let vertex_arrays_sphere = obj_sphere.get_vertex_array();
+    let mut camera = scene.camera.clone();
 
+    let mut raster_state = RasterState {
+        cull_mode: CullMode::Back,
+        front_face: FrontFace::CounterClockwise,
+        tiled: true,
+        ..RasterState::default()
+    };
 
+    let mut lighting = scene.lighting.clone();
 
     let mut time = 0;
-    let mut shader_actual = 1;
+
+    // Con --shader se cambia de planeta con los numeros, N y B. En una escena
+    // Tab elige el objeto al que M le cambia el ruido.
+    let mut shader_actual = options.shader.as_deref().and_then(|name| registry.index_of(name));
+    let mut seleccionado = 0;
+    let mut titulo = String::new();
 
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
             break;
         }
 
-        if window.is_key_down(Key::Key1) {
-            shader_actual = 1;
-        }
-        if window.is_key_down(Key::Key2) {
-            shader_actual = 2;
-        }
-        if window.is_key_down(Key::Key3) {
-            shader_actual = 3;
+        if let Some(actual) = shader_actual.as_mut() {
+            let anterior = *actual;
+            let teclas = [
+                Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5,
+                Key::Key6, Key::Key7, Key::Key8, Key::Key9, Key::Key0,
+            ];
+            for (index, tecla) in teclas.iter().enumerate() {
+                if window.is_key_down(*tecla) && index < registry.len() {
+                    *actual = index;
+                }
+            }
+            if window.is_key_pressed(Key::N, KeyRepeat::No) {
+                *actual = registry.next(*actual);
+            }
+            if window.is_key_pressed(Key::B, KeyRepeat::No) {
+                *actual = registry.previous(*actual);
+            }
+            if *actual != anterior {
+                if let Some(entry) = registry.entry(*actual) {
+                    cambiar_planeta(&mut scene.objects[0], entry);
+                }
+            }
+        } else if window.is_key_pressed(Key::Tab, KeyRepeat::No) && !scene.objects.is_empty() {
+            seleccionado = (seleccionado + 1) % scene.objects.len();
         }
-        if window.is_key_down(Key::Key4) {
-            shader_actual = 4;
+
+        if window.is_key_pressed(Key::M, KeyRepeat::No) {
+            let mut nombres: Vec<&str> = scene.noise.names().collect();
+            nombres.extend(noise_library.names().filter(|name| !scene.noise.contains(name)));
+            if let Some(object) = scene.objects.get_mut(seleccionado) {
+                object.noise = ruido_siguiente(object.noise.as_deref(), &nombres);
+            }
         }
-        if window.is_key_down(Key::Key5) {
-            shader_actual = 5;
+
+        if window.is_key_pressed(Key::C, KeyRepeat::No) {
+            raster_state.cull_mode = raster_state.cull_mode.next();
         }
-        if window.is_key_down(Key::Key6) {
-            shader_actual = 6;
+        if window.is_key_pressed(Key::V, KeyRepeat::No) {
+            raster_state.front_face = raster_state.front_face.flip();
         }
-        if window.is_key_down(Key::Key7) {
-            shader_actual = 7;
+        if window.is_key_pressed(Key::T, KeyRepeat::No) {
+            raster_state.tiled = !raster_state.tiled;
         }
-        if window.is_key_down(Key::Key8) {
-            shader_actual = 8;
+        if window.is_key_pressed(Key::L, KeyRepeat::No) {
+            lighting.model = lighting.model.toggle();
+            println!("modelo especular: {:?}", lighting.model);
         }
-        if window.is_key_down(Key::Key9) {
-            shader_actual = 9;
+        let screenshot = window.is_key_pressed(Key::P, KeyRepeat::No);
+
+        if let Some(watcher) = watcher.as_mut() {
+            if watcher.changed() {
+                match cargar_escena(watcher.path(), &registry, &noise_library) {
+                    Ok(nueva) => {
+                        // La camara solo se reinicia si el archivo la cambio; si
+                        // no, se conserva la que el usuario movio.
+                        let anterior = &scene.camera;
+                        if (nueva.camera.ojo, nueva.camera.centro, nueva.camera.sube) != (anterior.ojo, anterior.centro, anterior.sube) {
+                            camera = nueva.camera.clone();
+                        }
+                        lighting = nueva.lighting.clone();
+                        scene = nueva;
+                        if seleccionado >= scene.objects.len() {
+                            seleccionado = 0;
+                        }
+                        println!("escena recargada: {}", watcher.path().display());
+                    }
+                    Err(error) => eprintln!("no se pudo recargar la escena: {}", error),
+                }
+            }
         }
-        if window.is_key_down(Key::Key0) {
-            shader_actual = 0;
+
+        let nuevo_titulo = titulo_ventana(&scene, seleccionado, &registry, watcher.as_ref().map(FileWatcher::path));
+        if nuevo_titulo != titulo {
+            window.set_title(&nuevo_titulo);
+            titulo = nuevo_titulo;
         }
 
         time += 1;
 
         handle_input(&window, &mut camera);
 
-        framebuffer.clear();
-
-        let model_matrix = create_model_matrix(translation, scale, rotation);
-        let model_matrix_anillos = create_model_matrix(translation, scale, rotation_anillos);
-        let view_matrix = create_view_matrix(camera.ojo, camera.centro, camera.sube);
-        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-
-  
-
-        let uniforms_perlin = Uniforms { 
-            model_matrix: model_matrix.clone(), 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_perlin() 
-        };
-
- 
-
-        let uniforms_variado = Uniforms {
-            model_matrix: model_matrix_anillos, 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_variado() 
-        };
-
-  
-
-        let uniforms_camo = Uniforms { 
-            model_matrix: model_matrix.clone(), 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_camo() 
-        };
-
-        let uniforms_cellular_puntas = Uniforms { 
-            model_matrix: model_matrix.clone(), 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_cellular_puntas() 
-        };
-
-        let uniforms_cellular_agujero_negro = Uniforms { 
-            model_matrix: model_matrix.clone(), 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_cellular_agujero_negro() 
-        };
-
-        let uniforms_cellular_bacteria = Uniforms { 
-            model_matrix: model_matrix.clone(), 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_cellular_bacteria() 
-        };
-
-        let uniforms_cellular_grupos = Uniforms { 
-            model_matrix: model_matrix.clone(), 
-            view_matrix: view_matrix.clone(), 
-            projection_matrix: projection_matrix.clone(), 
-            viewport_matrix: viewport_matrix.clone(),
-            time,
-            noise: crear_ruido_grupos() 
-        };
-
-    
-
-        framebuffer.set_current_color(0xFFDDDD);
+        let frame_context = FrameContext { camera: &camera, lighting: &lighting, sun_position: scene.sun_position, time };
+        render_scene(&mut framebuffer, &frame_context, &scene, &registry, &noise_library, &raster_state);
 
-        match shader_actual {
-            1 => {
-            render_shader(&mut framebuffer, &uniforms_perlin, &vertex_arrays_sphere, hielo_shader);
-            }
-            2 => render_shader(&mut framebuffer, &uniforms_cellular_puntas, &vertex_arrays_sphere, volcanico_shader),
-            3 => render_shader(&mut framebuffer, &uniforms_perlin, &vertex_arrays_sphere, oceano_profundo_shader),
-            4 => render_shader(&mut framebuffer, &uniforms_cellular_grupos, &vertex_arrays_sphere, desierto_shader),
-       
-            5 => render_shader(&mut framebuffer, &uniforms_perlin, &vertex_arrays_sphere, shader_agua),
-            6 => render_shader(&mut framebuffer, &uniforms_cellular_bacteria, &vertex_arrays_sphere, jungla_shader),
-            7 => render_shader(&mut framebuffer, &uniforms_camo, &vertex_arrays_sphere, metano_shader),
-            8 => render_shader(&mut framebuffer, &uniforms_cellular_agujero_negro, &vertex_arrays_sphere, rocoso_montanoso_shader),
-            9 => render_shader(&mut framebuffer, &uniforms_variado, &vertex_arrays_sphere, aurora_shader),
-            _ => render_shader(&mut framebuffer, &uniforms_perlin, &vertex_arrays_sphere, crateres_shader),
+        if screenshot {
+            save_screenshot(&framebuffer);
         }
 
         window
             .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
             .unwrap();
-
-        std::thread::sleep(frame_delay);
     }
 }
 
-fn render_shader(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    fragment_shader_fn: fn(&Fragment, &Uniforms) -> Color
-) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
-
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+/// Guarda el cuadro actual como `screenshot_<segundos>_<milisegundos>.png`
+/// en el directorio de trabajo.
+fn save_screenshot(framebuffer: &Framebuffer) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let path = PathBuf::from(format!("screenshot_{}_{:03}.png", now.as_secs(), now.subsec_millis()));
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = fragment_shader_fn(&fragment, &uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
+    match framebuffer.save(&path) {
+        Ok(()) => println!("captura guardada en {}", path.display()),
+        Err(error) => eprintln!("no se pudo guardar {}: {}", path.display(), error),
     }
 }
 
- 
-
 fn handle_input(window: &Window, camera: &mut Camera) {
     let movement_speed = 1.0;
     let rotation_speed = PI/50.0;
@@ -419,62 +580,86 @@ fn handle_input(window: &Window, camera: &mut Camera) {
     }
 }
 
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
-    let (sin_x, cos_x) = rotation.x.sin_cos();
-    let (sin_y, cos_y) = rotation.y.sin_cos();
-    let (sin_z, cos_z) = rotation.z.sin_cos();
-
-    let rotation_matrix_x = Mat4::new(
-        1.0,  0.0,    0.0,   0.0,
-        0.0,  cos_x, -sin_x, 0.0,
-        0.0,  sin_x,  cos_x, 0.0,
-        0.0,  0.0,    0.0,   1.0,
-    );
-
-    let rotation_matrix_y = Mat4::new(
-        cos_y,  0.0,  sin_y, 0.0,
-        0.0,    1.0,  0.0,   0.0,
-        -sin_y, 0.0,  cos_y, 0.0,
-        0.0,    0.0,  0.0,   1.0,
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lab4::ruido::presets;
 
-    let rotation_matrix_z = Mat4::new(
-        cos_z, -sin_z, 0.0, 0.0,
-        sin_z,  cos_z, 0.0, 0.0,
-        0.0,    0.0,  1.0, 0.0,
-        0.0,    0.0,  0.0, 1.0,
-    );
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(args.into_iter())
+    }
 
-    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+    #[test]
+    fn defaults() {
+        let options = parse(&[]).unwrap();
+        assert!(!options.help && !options.headless && !options.list_shaders && !options.depth);
+        assert!(options.scene.is_none() && options.shader.is_none() && options.mesh.is_none());
+        assert!(options.noise.is_none() && options.noise_dir.is_none() && options.seed.is_none());
+        assert_eq!(options.window, (1000, 800));
+        assert_eq!(options.resolution, None);
+        assert_eq!(options.fps, 60);
+        assert_eq!(options.frames, 1);
+        assert_eq!(options.output, PathBuf::from("output"));
+        assert_eq!(options.format, ImageFormat::Png);
+        assert!(validar(&options, &planetas(), &presets()).is_ok());
+    }
 
-    let transform_matrix = Mat4::new(
-        scale, 0.0,   0.0,   translation.x,
-        0.0,   scale, 0.0,   translation.y,
-        0.0,   0.0,   scale, translation.z,
-        0.0,   0.0,   0.0,   1.0,
-    );
+    #[test]
+    fn values_are_parsed() {
+        let options = parse(&[
+            "--shader", "agua", "--window", "640x480", "--resolution", "320x240", "--fps", "0",
+            "--frames", "3", "--seed", "7", "--format", "bmp", "--headless", "--depth",
+        ])
+        .unwrap();
+        assert_eq!(options.shader.as_deref(), Some("agua"));
+        assert_eq!(options.window, (640, 480));
+        assert_eq!(options.resolution, Some((320, 240)));
+        assert_eq!((options.fps, options.frames, options.seed), (0, 3, Some(7)));
+        assert_eq!(options.format, ImageFormat::Bmp);
+        assert!(options.headless && options.depth);
+    }
 
-    transform_matrix * rotation_matrix
-}
+    #[test]
+    fn invalid_arguments_explain_the_problem() {
+        let casos: [(&[&str], &str); 15] = [
+            (&["--window", "0x4"], "--window debe ser ANCHOxALTO con enteros positivos, como 1000x800, no 0x4"),
+            (&["--resolution", "800"], "--resolution debe ser ANCHOxALTO con enteros positivos, como 1000x800, no 800"),
+            (&["--fps"], "falta el valor de --fps"),
+            (&["--fps", "rapido"], "--fps debe ser un entero (0 sin limite)"),
+            (&["--frames", "0"], "--frames debe ser un entero positivo"),
+            (&["--seed", "-1"], "--seed debe ser un entero entre 0 y 4294967295"),
+            (&["--format", "gif"], "formato no soportado: gif (usar png, ppm o bmp)"),
+            (&["--planeta"], "argumento desconocido: --planeta"),
+            (&["--scene", "a.toml", "--shader", "agua"], "--scene y --shader no se pueden usar juntos"),
+            (&["--mesh", "a.obj"], "--mesh solo se usa con --shader; las mallas de una escena van en su archivo"),
+            (&["--frames", "3"], "--frames solo se usa con --headless"),
+            (&["--output", "capturas"], "--output solo se usa con --headless"),
+            (&["--format", "bmp"], "--format solo se usa con --headless"),
+            (&["--depth"], "--depth solo se usa con --headless"),
+            (&["--depth", "--frames", "3", "--shader", "agua"], "--depth solo se usa con --headless"),
+        ];
+        for (args, esperado) in casos {
+            assert_eq!(parse(args).err().as_deref(), Some(esperado), "{:?}", args);
+        }
+    }
 
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
-}
+    #[test]
+    fn unknown_shader_noise_and_missing_mesh_are_reported() {
+        let (registry, noise_library) = (planetas(), presets());
+        let error = |args: &[&str]| validar(&parse(args).unwrap(), &registry, &noise_library).unwrap_err();
 
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
-    let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
+        assert_eq!(error(&["--shader", "plutonio"]), "shader desconocido: plutonio (ver --list-shaders)");
+        assert_eq!(error(&["--shader", "agua", "--mesh", "no/existe.obj"]), "no existe la malla no/existe.obj");
+        assert!(error(&["--shader", "agua", "--noise", "nada"]).starts_with("ruido desconocido: nada (disponibles: "));
+    }
 
-    perspective(fov, aspect_ratio, near, far)
+    #[test]
+    fn noise_cycle_returns_to_the_shader_default() {
+        let nombres = ["a", "b"];
+        assert_eq!(ruido_siguiente(None, &nombres).as_deref(), Some("a"));
+        assert_eq!(ruido_siguiente(Some("a"), &nombres).as_deref(), Some("b"));
+        assert_eq!(ruido_siguiente(Some("b"), &nombres), None);
+        assert_eq!(ruido_siguiente(Some("otro"), &nombres).as_deref(), Some("a"));
+    }
 }
-
-fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
-    Mat4::new(
-        width / 2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
-        0.0, 0.0, 1.0, 0.0,
-        0.0, 0.0, 0.0, 1.0
-    )
-}
\ No newline at end of file
@@ -0,0 +1,37 @@
+//! Mallas generadas por codigo.
+
+use std::f32::consts::PI;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::vertex::Vertex;
+
+/// Anillo plano (un disco con hueco) en el plano xz, centrado en el origen y
+/// con la normal hacia +y, como arreglo de vertices de tres en tres.
+///
+/// `tex_coords.x` va de 0 en el borde interior a 1 en el exterior y
+/// `tex_coords.y` de 0 a 1 alrededor del anillo, asi el shader puede dibujar
+/// bandas radiales sin calcular distancias.
+pub fn anillo(radio_interior: f32, radio_exterior: f32, segmentos: u32) -> Vec<Vertex> {
+    let segmentos = segmentos.max(3);
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let punto = |radio: f32, paso: u32| {
+        let angulo = paso as f32 / segmentos as f32 * 2.0 * PI;
+        Vec3::new(radio * angulo.cos(), 0.0, -radio * angulo.sin())
+    };
+    let vertice = |radio: f32, paso: u32, u: f32| {
+        Vertex::new(punto(radio, paso), normal, Vec2::new(u, paso as f32 / segmentos as f32))
+    };
+
+    let mut vertices = Vec::with_capacity(segmentos as usize * 6);
+    for paso in 0..segmentos {
+        let interior_a = vertice(radio_interior, paso, 0.0);
+        let exterior_a = vertice(radio_exterior, paso, 1.0);
+        let interior_b = vertice(radio_interior, paso + 1, 0.0);
+        let exterior_b = vertice(radio_exterior, paso + 1, 1.0);
+
+        // Antihorario visto desde +y.
+        vertices.extend([interior_a.clone(), exterior_a, exterior_b.clone()]);
+        vertices.extend([interior_a, exterior_b, interior_b]);
+    }
+
+    vertices
+}
@@ -0,0 +1,215 @@
+//! El trait [`NoiseSource`] sobre `fastnoise-lite` y el crate `noise`, con
+//! combinadores fractales.
+
+use fastnoise_lite::FastNoiseLite;
+use noise::NoiseFn;
+
+/// Una fuente de ruido con valores aproximadamente en [-1, 1].
+///
+/// Los shaders solo dependen de este trait, asi que el mismo shader puede usar
+/// `fastnoise-lite`, cualquier generador del crate `noise` (con [`NoiseRs`]) o
+/// una combinacion de ellos ([`Fbm`], [`Ridged`], [`DomainWarp`]).
+pub trait NoiseSource: Sync {
+    /// Valor en el punto (x, y).
+    fn sample_2d(&self, x: f32, y: f32) -> f32;
+    /// Valor en el punto (x, y, z).
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32;
+}
+
+impl NoiseSource for FastNoiseLite {
+    fn sample_2d(&self, x: f32, y: f32) -> f32 {
+        self.get_noise_2d(x, y)
+    }
+
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.get_noise_3d(x, y, z)
+    }
+}
+
+impl<S: NoiseSource + ?Sized> NoiseSource for Box<S> {
+    fn sample_2d(&self, x: f32, y: f32) -> f32 {
+        (**self).sample_2d(x, y)
+    }
+
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        (**self).sample_3d(x, y, z)
+    }
+}
+
+/// Adaptador para los generadores del crate `noise` (`Perlin`, `Simplex`,
+/// `OpenSimplex`, `Worley`, ...). Las coordenadas se multiplican por
+/// `frequency` antes de muestrear, igual que en `fastnoise-lite`.
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseRs<T> {
+    /// El generador del crate `noise`.
+    pub generator: T,
+    /// Factor por el que se multiplican las coordenadas.
+    pub frequency: f32,
+}
+
+impl<T> NoiseRs<T> {
+    /// Adapta `generator` muestreandolo con `frequency`.
+    pub fn new(generator: T, frequency: f32) -> Self {
+        NoiseRs { generator, frequency }
+    }
+}
+
+impl<T> NoiseSource for NoiseRs<T>
+where
+    T: NoiseFn<f64, 2> + NoiseFn<f64, 3> + Sync,
+{
+    fn sample_2d(&self, x: f32, y: f32) -> f32 {
+        let f = self.frequency as f64;
+        NoiseFn::<f64, 2>::get(&self.generator, [x as f64 * f, y as f64 * f]) as f32
+    }
+
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        let f = self.frequency as f64;
+        NoiseFn::<f64, 3>::get(&self.generator, [x as f64 * f, y as f64 * f, z as f64 * f]) as f32
+    }
+}
+
+/// Movimiento browniano fraccional: suma `octaves` copias de `source`, cada
+/// una con la frecuencia multiplicada por `lacunarity` y la amplitud por
+/// `gain`. El resultado se normaliza para seguir en [-1, 1].
+#[derive(Clone, Copy, Debug)]
+pub struct Fbm<S> {
+    /// Ruido de la primera octava.
+    pub source: S,
+    /// Cantidad de octavas.
+    pub octaves: u32,
+    /// Factor de frecuencia entre una octava y la siguiente.
+    pub lacunarity: f32,
+    /// Factor de amplitud entre una octava y la siguiente.
+    pub gain: f32,
+}
+
+impl<S: NoiseSource> Fbm<S> {
+    /// fBm de `source` con estos parametros.
+    pub fn new(source: S, octaves: u32, lacunarity: f32, gain: f32) -> Self {
+        Fbm { source, octaves, lacunarity, gain }
+    }
+
+    fn accumulate(&self, mut sample: impl FnMut(f32) -> f32) -> f32 {
+        let mut total = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut max_value = 0.0;
+
+        for _ in 0..self.octaves {
+            total += sample(frequency) * amplitude;
+            max_value += amplitude;
+
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        if max_value == 0.0 { 0.0 } else { total / max_value }
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Fbm<S> {
+    fn sample_2d(&self, x: f32, y: f32) -> f32 {
+        self.accumulate(|f| self.source.sample_2d(x * f, y * f))
+    }
+
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.accumulate(|f| self.source.sample_3d(x * f, y * f, z * f))
+    }
+}
+
+/// Ruido multifractal "ridged": cada octava usa `1 - |n|` al cuadrado, lo que
+/// produce crestas finas como cordilleras, y pesa la siguiente octava con la
+/// anterior para que el detalle se concentre en las crestas.
+#[derive(Clone, Copy, Debug)]
+pub struct Ridged<S> {
+    /// Ruido de la primera octava.
+    pub source: S,
+    /// Cantidad de octavas.
+    pub octaves: u32,
+    /// Factor de frecuencia entre una octava y la siguiente.
+    pub lacunarity: f32,
+    /// Factor de amplitud entre una octava y la siguiente.
+    pub gain: f32,
+}
+
+impl<S: NoiseSource> Ridged<S> {
+    /// Ruido ridged de `source` con estos parametros.
+    pub fn new(source: S, octaves: u32, lacunarity: f32, gain: f32) -> Self {
+        Ridged { source, octaves, lacunarity, gain }
+    }
+
+    fn accumulate(&self, mut sample: impl FnMut(f32) -> f32) -> f32 {
+        let mut total = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut weight = 1.0;
+        let mut max_value = 0.0;
+
+        for _ in 0..self.octaves {
+            let ridge = 1.0 - sample(frequency).abs();
+            let ridge = ridge * ridge * weight;
+            weight = (ridge * 2.0).clamp(0.0, 1.0);
+
+            total += ridge * amplitude;
+            max_value += amplitude;
+
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        if max_value == 0.0 { 0.0 } else { total / max_value * 2.0 - 1.0 }
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Ridged<S> {
+    fn sample_2d(&self, x: f32, y: f32) -> f32 {
+        self.accumulate(|f| self.source.sample_2d(x * f, y * f))
+    }
+
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.accumulate(|f| self.source.sample_3d(x * f, y * f, z * f))
+    }
+}
+
+// Desplazamientos para que cada eje del warp lea una zona distinta del ruido.
+const WARP_Y: (f32, f32, f32) = (5.2, 1.3, 7.1);
+const WARP_Z: (f32, f32, f32) = (1.7, 9.2, 3.4);
+
+/// Deforma el dominio: antes de muestrear `source` desplaza el punto con tres
+/// lecturas de `warp` (una por eje) escaladas por `amplitude`.
+#[derive(Clone, Copy, Debug)]
+pub struct DomainWarp<S, W> {
+    /// Ruido que se muestrea en el punto desplazado.
+    pub source: S,
+    /// Ruido que da el desplazamiento en cada eje.
+    pub warp: W,
+    /// Desplazamiento maximo, en las unidades de las coordenadas.
+    pub amplitude: f32,
+}
+
+impl<S: NoiseSource, W: NoiseSource> DomainWarp<S, W> {
+    /// Deforma `source` con `warp` escalado por `amplitude`.
+    pub fn new(source: S, warp: W, amplitude: f32) -> Self {
+        DomainWarp { source, warp, amplitude }
+    }
+}
+
+impl<S: NoiseSource, W: NoiseSource> NoiseSource for DomainWarp<S, W> {
+    fn sample_2d(&self, x: f32, y: f32) -> f32 {
+        let dx = self.warp.sample_2d(x, y);
+        let dy = self.warp.sample_2d(x + WARP_Y.0, y + WARP_Y.1);
+        self.source.sample_2d(x + dx * self.amplitude, y + dy * self.amplitude)
+    }
+
+    fn sample_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        let dx = self.warp.sample_3d(x, y, z);
+        let dy = self.warp.sample_3d(x + WARP_Y.0, y + WARP_Y.1, z + WARP_Y.2);
+        let dz = self.warp.sample_3d(x + WARP_Z.0, y + WARP_Z.1, z + WARP_Z.2);
+        self.source.sample_3d(
+            x + dx * self.amplitude,
+            y + dy * self.amplitude,
+            z + dz * self.amplitude,
+        )
+    }
+}
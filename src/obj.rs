@@ -1,7 +1,9 @@
-use tobj;
+//! Carga de mallas desde archivos Wavefront `.obj`.
+
 use nalgebra_glm::{Vec2, Vec3};
 use crate::vertex::Vertex;
 
+/// Una malla cargada de un archivo `.obj`.
 pub struct Obj {
     meshes: Vec<Mesh>,
 }
@@ -14,6 +16,8 @@ struct Mesh {
 }
 
 impl Obj {
+    /// Carga un `.obj` triangulando sus caras. La coordenada v de textura se
+    /// invierte para que 0 quede arriba.
     pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
         let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
             single_index: true,
@@ -40,6 +44,8 @@ impl Obj {
         Ok(Obj { meshes })
     }
 
+    /// Todos los triangulos de la malla como una lista plana de vertices,
+    /// tres por triangulo.
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
         let mut vertices = Vec::new();
 
@@ -0,0 +1,306 @@
+//! Matrices, uniforms y el dibujo de mallas, planetas y escenas.
+
+use nalgebra_glm::{Vec3, Mat4, look_at, perspective, scaling};
+use std::f32::consts::PI;
+use crate::camera::Camera;
+use crate::clipping::{clip_triangle, inside_frustum, to_screen};
+use crate::fragment::Fragment;
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::lighting::Lighting;
+use crate::noise_source::NoiseSource;
+use crate::registry::{ShaderEntry, ShaderRegistry};
+use crate::ruido::NoiseLibrary;
+use crate::scene::{Scene, Transform};
+use crate::shaders::{vertex_shader, FragmentShader};
+use crate::tiles::render_tiled;
+use crate::triangle::{triangle, CullMode, RasterState};
+use crate::vertex::Vertex;
+
+/// Datos compartidos por todos los vertices y fragmentos de una llamada de
+/// dibujo.
+pub struct Uniforms<'a> {
+    /// Del espacio del objeto al de mundo.
+    pub model_matrix: Mat4,
+    /// Del mundo al espacio de la camara.
+    pub view_matrix: Mat4,
+    /// De la camara a clip space.
+    pub projection_matrix: Mat4,
+    /// De NDC a pixeles.
+    pub viewport_matrix: Mat4,
+    /// Cuadro actual; anima el ruido de los shaders.
+    pub time: u32,
+    /// Ruido del objeto que se dibuja.
+    pub noise: &'a dyn NoiseSource,
+    /// Luces de la escena.
+    pub lighting: &'a Lighting,
+    /// Posicion de la camara en espacio de mundo, para el brillo especular.
+    pub camera_position: Vec3,
+    /// Posicion del sol en espacio de mundo; separa el lado de dia del de
+    /// noche (ver `shaders::LucesNocturnas`).
+    pub sun_position: Vec3,
+}
+
+/// Lo que cambia de un cuadro a otro y comparten todos los objetos: desde
+/// donde se mira, con que luces, donde esta el sol y en que instante.
+pub struct FrameContext<'a> {
+    /// Camara desde la que se mira.
+    pub camera: &'a Camera,
+    /// Luces de la escena.
+    pub lighting: &'a Lighting,
+    /// Posicion del sol, la que separa el dia de la noche en los shaders.
+    pub sun_position: Vec3,
+    /// Cuadro actual.
+    pub time: u32,
+}
+
+/// Limpia el framebuffer y dibuja la malla con un shader del registro y su
+/// transformacion por defecto. El ruido se recibe aparte para poder probar un
+/// shader con otro preset distinto del que tiene registrado.
+///
+/// Si el planeta tiene atmosfera se dibuja despues, sobre la misma malla
+/// agrandada, con mezcla aditiva y sin escribir profundidad. Los anillos van
+/// al final con la matriz de modelo del planeta: se mezclan con alfa, se ven
+/// de ambos lados y el z-buffer del planeta tapa la parte que pasa detras.
+pub fn render_frame(
+    framebuffer: &mut Framebuffer,
+    frame: &FrameContext,
+    vertex_array: &[Vertex],
+    raster_state: &RasterState,
+    entry: &ShaderEntry,
+    noise: &dyn NoiseSource,
+) {
+    let transform = Transform {
+        rotation: entry.defaults.rotation,
+        scale: entry.defaults.scale,
+        ..Transform::default()
+    };
+
+    framebuffer.clear();
+
+    let uniforms = frame_uniforms(framebuffer, frame, transform.model_matrix(), noise);
+    render_shader(framebuffer, &uniforms, vertex_array, raster_state, entry.shader.as_ref());
+    render_capas(framebuffer, &uniforms, vertex_array, raster_state, entry);
+}
+
+/// Limpia el framebuffer con el fondo de la escena y dibuja todos sus objetos
+/// sobre el mismo z-buffer, ubicados segun `Scene::model_matrices` en
+/// `frame.time`. Primero van los cuerpos opacos y despues las
+/// atmosferas y anillos, del objeto mas lejano al mas cercano, para que cada
+/// capa translucida se mezcle con lo que ya esta detras.
+///
+/// Los objetos con una malla, shader o ruido que no existe no se dibujan (ver
+/// `Scene::validate`).
+pub fn render_scene(
+    framebuffer: &mut Framebuffer,
+    frame: &FrameContext,
+    scene: &Scene,
+    registry: &ShaderRegistry,
+    noise_library: &NoiseLibrary,
+    raster_state: &RasterState,
+) {
+    framebuffer.set_background_color(scene.background);
+    framebuffer.clear();
+
+    let objetos: Vec<_> = scene
+        .objects
+        .iter()
+        .zip(scene.model_matrices(frame.time))
+        .filter_map(|(object, model_matrix)| {
+            let vertex_array = scene.mesh(&object.mesh)?;
+            let entry = registry.get(&object.shader)?;
+            let noise = scene.noise(object.noise.as_deref().unwrap_or(&entry.noise), noise_library)?;
+            Some((model_matrix, vertex_array, entry, noise))
+        })
+        .collect();
+
+    for &(model_matrix, vertex_array, entry, noise) in &objetos {
+        let uniforms = frame_uniforms(framebuffer, frame, model_matrix, noise);
+        render_shader(framebuffer, &uniforms, vertex_array, raster_state, entry.shader.as_ref());
+    }
+
+    let distancia = |model_matrix: &Mat4| (model_matrix.column(3).xyz() - frame.camera.ojo).magnitude();
+    let mut translucidos: Vec<_> = objetos
+        .iter()
+        .filter(|(_, _, entry, _)| entry.defaults.atmosfera.is_some() || entry.defaults.anillos.is_some())
+        .collect();
+    translucidos.sort_by(|a, b| distancia(&b.0).total_cmp(&distancia(&a.0)));
+
+    for &&(model_matrix, vertex_array, entry, noise) in &translucidos {
+        let uniforms = frame_uniforms(framebuffer, frame, model_matrix, noise);
+        render_capas(framebuffer, &uniforms, vertex_array, raster_state, entry);
+    }
+}
+
+/// Uniforms de un objeto con las matrices de camara del cuadro.
+fn frame_uniforms<'a>(
+    framebuffer: &Framebuffer,
+    frame: &FrameContext<'a>,
+    model_matrix: Mat4,
+    noise: &'a dyn NoiseSource,
+) -> Uniforms<'a> {
+    let camera = frame.camera;
+    Uniforms {
+        model_matrix,
+        view_matrix: create_view_matrix(camera.ojo, camera.centro, camera.sube),
+        projection_matrix: create_perspective_matrix(framebuffer.width as f32, framebuffer.height as f32),
+        viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+        time: frame.time,
+        noise,
+        lighting: frame.lighting,
+        camera_position: camera.ojo,
+        sun_position: frame.sun_position,
+    }
+}
+
+/// Dibuja la atmosfera y los anillos de un objeto, si tiene, sobre lo que ya
+/// esta en el framebuffer. `uniforms` son las del cuerpo del objeto.
+fn render_capas(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    raster_state: &RasterState,
+    entry: &ShaderEntry,
+) {
+    if let Some(atmosfera) = &entry.defaults.atmosfera {
+        let uniforms = Uniforms {
+            model_matrix: uniforms.model_matrix * scaling(&Vec3::repeat(1.0 + atmosfera.grosor)),
+            ..*uniforms
+        };
+        let capa = RasterState {
+            cull_mode: CullMode::Back,
+            blend: BlendMode::Additive,
+            depth_write: false,
+            ..*raster_state
+        };
+        render_shader(framebuffer, &uniforms, vertex_array, &capa, atmosfera);
+    }
+
+    if let Some(anillos) = &entry.defaults.anillos {
+        let capa = RasterState {
+            cull_mode: CullMode::None,
+            blend: BlendMode::Alpha,
+            depth_write: false,
+            ..*raster_state
+        };
+        render_shader(framebuffer, uniforms, entry.malla_anillos(), &capa, &anillos.shader);
+    }
+}
+
+/// Dibuja un arreglo de vertices (tres por triangulo) con un fragment shader:
+/// vertex shader, recorte contra el frustum, division perspectiva y
+/// rasterizacion por tiles o en un solo hilo segun `raster_state`.
+pub fn render_shader(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    raster_state: &RasterState,
+    fragment_shader: &dyn FragmentShader,
+) {
+    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
+    for vertex in vertex_array {
+        let transformed = vertex_shader(vertex, uniforms);
+        transformed_vertices.push(transformed);
+    }
+
+    let mut triangles = Vec::with_capacity(transformed_vertices.len() / 3);
+    let viewport = &uniforms.viewport_matrix;
+    for tri in transformed_vertices.chunks_exact(3) {
+        // Casi todos los triangulos quedan enteros adentro del frustum; solo
+        // los que cruzan un plano pasan por el recorte.
+        if tri.iter().all(inside_frustum) {
+            triangles.push([to_screen(&tri[0], viewport), to_screen(&tri[1], viewport), to_screen(&tri[2], viewport)]);
+            continue;
+        }
+
+        let polygon = clip_triangle(&tri[0], &tri[1], &tri[2]);
+        if polygon.len() < 3 {
+            continue;
+        }
+
+        // El poligono recortado es convexo: se dibuja como un abanico desde
+        // el primer vertice.
+        let primero = to_screen(&polygon[0], viewport);
+        let mut anterior = to_screen(&polygon[1], viewport);
+        for vertex in &polygon[2..] {
+            let actual = to_screen(vertex, viewport);
+            triangles.push([primero.clone(), anterior, actual.clone()]);
+            anterior = actual;
+        }
+    }
+
+    let shade = |fragment: &Fragment| fragment_shader.shade_alpha(fragment, uniforms);
+
+    if raster_state.tiled {
+        render_tiled(framebuffer, &triangles, raster_state, &shade);
+    } else {
+        for [v1, v2, v3] in &triangles {
+            triangle(v1, v2, v3, raster_state, framebuffer, shade);
+        }
+    }
+}
+
+/// Matriz de modelo: escala uniforme, rotacion de Euler (x, luego y, luego z)
+/// y traslacion.
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0,  0.0,    0.0,   0.0,
+        0.0,  cos_x, -sin_x, 0.0,
+        0.0,  sin_x,  cos_x, 0.0,
+        0.0,  0.0,    0.0,   1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y,  0.0,  sin_y, 0.0,
+        0.0,    1.0,  0.0,   0.0,
+        -sin_y, 0.0,  cos_y, 0.0,
+        0.0,    0.0,  0.0,   1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0,
+        sin_z,  cos_z, 0.0, 0.0,
+        0.0,    0.0,  1.0, 0.0,
+        0.0,    0.0,  0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let transform_matrix = Mat4::new(
+        scale, 0.0,   0.0,   translation.x,
+        0.0,   scale, 0.0,   translation.y,
+        0.0,   0.0,   scale, translation.z,
+        0.0,   0.0,   0.0,   1.0,
+    );
+
+    transform_matrix * rotation_matrix
+}
+
+/// Matriz de vista de una camara en `eye` que mira a `center`, con `up`
+/// hacia arriba.
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+/// Proyeccion con 45 grados de campo de vision y planos en 0.1 y 1000.
+pub fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
+    let fov = 45.0 * PI / 180.0;
+    let aspect_ratio = window_width / window_height;
+    let near = 0.1;
+    let far = 1000.0;
+
+    perspective(aspect_ratio, fov, near, far)
+}
+
+/// Lleva NDC a pixeles, con el eje y hacia abajo.
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, width / 2.0,
+        0.0, -height / 2.0, 0.0, height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
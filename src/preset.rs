@@ -0,0 +1,187 @@
+//! Presets de ruido de `fastnoise-lite` descritos en TOML.
+
+use std::path::Path;
+use fastnoise_lite::{CellularDistanceFunction, CellularReturnType, FastNoiseLite, FractalType, NoiseType};
+use serde::Deserialize;
+use crate::toml_file::{self, campo, elegir, no_negativo, positivo, TomlError};
+
+/// Un preset de ruido escrito en TOML, por ejemplo:
+///
+/// ```toml
+/// type = "cellular"
+/// seed = 1337
+/// frequency = 0.03
+///
+/// [fractal]
+/// type = "ping_pong"
+/// octaves = 3
+/// lacunarity = 2.0
+/// gain = 0.5
+/// ping_pong_strength = 1.0
+///
+/// [cellular]
+/// distance_function = "hybrid"
+/// return_type = "distance2_sub"
+/// jitter = 2.0
+/// ```
+///
+/// Los campos que faltan quedan con el valor por defecto de `fastnoise-lite`.
+/// Los nombres de tipos y funciones se escriben en snake_case.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NoisePreset {
+    /// Tipo de ruido, como `open_simplex2`, `perlin` o `cellular`.
+    #[serde(rename = "type")]
+    pub noise_type: Option<String>,
+    /// Semilla del generador.
+    pub seed: Option<i32>,
+    /// Frecuencia; debe ser mayor que 0.
+    pub frequency: Option<f32>,
+    /// La tabla `[fractal]`.
+    pub fractal: Option<FractalPreset>,
+    /// La tabla `[cellular]`, solo para ruido celular.
+    pub cellular: Option<CellularPreset>,
+}
+
+/// La tabla `[fractal]` de un preset.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FractalPreset {
+    /// Tipo de fractal: `none`, `fbm`, `ridged` o `ping_pong`.
+    #[serde(rename = "type")]
+    pub fractal_type: Option<String>,
+    /// Cantidad de octavas, entre 1 y 16.
+    pub octaves: Option<i32>,
+    /// Factor de frecuencia entre una octava y la siguiente.
+    pub lacunarity: Option<f32>,
+    /// Factor de amplitud entre una octava y la siguiente.
+    pub gain: Option<f32>,
+    /// Cuanto pesa cada octava segun el valor de la anterior.
+    pub weighted_strength: Option<f32>,
+    /// Intensidad del rebote de `ping_pong`.
+    pub ping_pong_strength: Option<f32>,
+}
+
+/// La tabla `[cellular]` de un preset.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CellularPreset {
+    /// Funcion de distancia, como `euclidean` o `hybrid`.
+    pub distance_function: Option<String>,
+    /// Valor que devuelve el ruido, como `distance` o `distance2_sub`.
+    pub return_type: Option<String>,
+    /// Cuanto se apartan los puntos de la grilla.
+    pub jitter: Option<f32>,
+}
+
+const TIPOS: [(&str, NoiseType); 6] = [
+    ("open_simplex2", NoiseType::OpenSimplex2),
+    ("open_simplex2s", NoiseType::OpenSimplex2S),
+    ("cellular", NoiseType::Cellular),
+    ("perlin", NoiseType::Perlin),
+    ("value_cubic", NoiseType::ValueCubic),
+    ("value", NoiseType::Value),
+];
+
+// Los tipos de domain warp de fastnoise-lite no producen ruido por si solos,
+// asi que no se aceptan aqui.
+const FRACTALES: [(&str, FractalType); 4] = [
+    ("none", FractalType::None),
+    ("fbm", FractalType::FBm),
+    ("ridged", FractalType::Ridged),
+    ("ping_pong", FractalType::PingPong),
+];
+
+const DISTANCIAS: [(&str, CellularDistanceFunction); 4] = [
+    ("euclidean", CellularDistanceFunction::Euclidean),
+    ("euclidean_sq", CellularDistanceFunction::EuclideanSq),
+    ("manhattan", CellularDistanceFunction::Manhattan),
+    ("hybrid", CellularDistanceFunction::Hybrid),
+];
+
+const RETORNOS: [(&str, CellularReturnType); 7] = [
+    ("cell_value", CellularReturnType::CellValue),
+    ("distance", CellularReturnType::Distance),
+    ("distance2", CellularReturnType::Distance2),
+    ("distance2_add", CellularReturnType::Distance2Add),
+    ("distance2_sub", CellularReturnType::Distance2Sub),
+    ("distance2_mul", CellularReturnType::Distance2Mul),
+    ("distance2_div", CellularReturnType::Distance2Div),
+];
+
+impl NoisePreset {
+    /// Lee un preset de TOML; los valores se revisan en `build`.
+    pub fn parse(texto: &str) -> Result<Self, TomlError> {
+        toml_file::parse(texto)
+    }
+
+    /// Valida el preset y arma el generador configurado.
+    pub fn build(&self) -> Result<FastNoiseLite, TomlError> {
+        let mut noise = FastNoiseLite::new();
+
+        let noise_type = match &self.noise_type {
+            Some(nombre) => Some(elegir("type", nombre, &TIPOS)?),
+            None => None,
+        };
+        noise.set_noise_type(noise_type);
+        noise.set_seed(self.seed);
+        if let Some(frequency) = self.frequency {
+            noise.set_frequency(Some(positivo("frequency", frequency)?));
+        }
+
+        if let Some(fractal) = &self.fractal {
+            let fractal_type = match &fractal.fractal_type {
+                Some(nombre) => elegir("fractal.type", nombre, &FRACTALES)?,
+                None => return Err(campo("fractal.type", "es obligatorio dentro de [fractal]")),
+            };
+            noise.set_fractal_type(Some(fractal_type));
+
+            if let Some(octaves) = fractal.octaves {
+                if !(1..=16).contains(&octaves) {
+                    return Err(campo("fractal.octaves", format!("debe estar entre 1 y 16, no {}", octaves)));
+                }
+                noise.set_fractal_octaves(Some(octaves));
+            }
+            if let Some(lacunarity) = fractal.lacunarity {
+                noise.set_fractal_lacunarity(Some(positivo("fractal.lacunarity", lacunarity)?));
+            }
+            if let Some(gain) = fractal.gain {
+                noise.set_fractal_gain(Some(no_negativo("fractal.gain", gain)?));
+            }
+            if let Some(weighted_strength) = fractal.weighted_strength {
+                noise.set_fractal_weighted_strength(Some(no_negativo("fractal.weighted_strength", weighted_strength)?));
+            }
+            if let Some(strength) = fractal.ping_pong_strength {
+                if fractal_type != FractalType::PingPong {
+                    return Err(campo("fractal.ping_pong_strength", "solo se usa con type = \"ping_pong\""));
+                }
+                noise.set_fractal_ping_pong_strength(Some(no_negativo("fractal.ping_pong_strength", strength)?));
+            }
+        }
+
+        if let Some(cellular) = &self.cellular {
+            if noise_type != Some(NoiseType::Cellular) {
+                return Err(campo("cellular", "solo se usa con type = \"cellular\""));
+            }
+            if let Some(nombre) = &cellular.distance_function {
+                noise.set_cellular_distance_function(Some(elegir("cellular.distance_function", nombre, &DISTANCIAS)?));
+            }
+            if let Some(nombre) = &cellular.return_type {
+                noise.set_cellular_return_type(Some(elegir("cellular.return_type", nombre, &RETORNOS)?));
+            }
+            if let Some(jitter) = cellular.jitter {
+                noise.set_cellular_jitter(Some(no_negativo("cellular.jitter", jitter)?));
+            }
+        }
+
+        Ok(noise)
+    }
+}
+
+/// Lee y valida un preset desde un archivo.
+pub fn load(path: &Path) -> Result<FastNoiseLite, TomlError> {
+    let texto = toml_file::leer(path)?;
+    NoisePreset::parse(&texto)
+        .and_then(|preset| preset.build())
+        .map_err(|error| TomlError::Archivo { path: path.to_path_buf(), error: Box::new(error) })
+}
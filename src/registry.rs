@@ -0,0 +1,197 @@
+//! Registro de shaders por nombre con su ruido, transformacion y capas.
+
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use crate::color::Color;
+use crate::mesh;
+use crate::shaders::{
+    AguaShader, AnillosShader, AtmosferaShader, AuroraShader, CrateresShader, DesiertoShader, EstrellaShader, FragmentShader,
+    HieloShader, JunglaShader, MetanoShader, OceanoProfundoShader, RocosoMontanosoShader, VolcanicoShader,
+};
+use crate::vertex::Vertex;
+
+/// Un sistema de anillos: la malla de `mesh::anillo` con estos radios, en
+/// unidades de la malla del planeta (la esfera de `assets/sphere.obj` tiene
+/// radio 0.5), y el shader que la pinta.
+#[derive(Clone, Copy, Debug)]
+pub struct Anillos {
+    /// Radio del borde interior.
+    pub radio_interior: f32,
+    /// Radio del borde exterior.
+    pub radio_exterior: f32,
+    /// Segmentos alrededor del anillo.
+    pub segmentos: u32,
+    /// Shader que pinta los anillos.
+    pub shader: AnillosShader,
+}
+
+impl Default for Anillos {
+    fn default() -> Self {
+        Anillos {
+            radio_interior: 0.7,
+            radio_exterior: 1.2,
+            segmentos: 128,
+            shader: AnillosShader::default(),
+        }
+    }
+}
+
+/// Transformacion del modelo con la que se muestra un shader por defecto, y
+/// su atmosfera y anillos si tiene.
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderDefaults {
+    /// Rotacion de Euler del modelo.
+    pub rotation: Vec3,
+    /// Escala uniforme del modelo.
+    pub scale: f32,
+    /// Atmosfera que se dibuja sobre el planeta, si tiene.
+    pub atmosfera: Option<AtmosferaShader>,
+    /// Anillos del planeta, si tiene.
+    pub anillos: Option<Anillos>,
+}
+
+impl Default for ShaderDefaults {
+    fn default() -> Self {
+        ShaderDefaults {
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            scale: 1.0,
+            atmosfera: None,
+            anillos: None,
+        }
+    }
+}
+
+/// Un shader registrado con el nombre del preset de ruido que usa (ver
+/// `ruido::NoiseLibrary`).
+pub struct ShaderEntry {
+    /// Nombre con el que se busca.
+    pub name: String,
+    /// El fragment shader.
+    pub shader: Box<dyn FragmentShader>,
+    /// Preset de ruido que usa si no se pide otro.
+    pub noise: String,
+    /// Transformacion y capas con las que se muestra.
+    pub defaults: ShaderDefaults,
+    /// La malla de `defaults.anillos`, armada una vez al registrar.
+    malla_anillos: Vec<Vertex>,
+}
+
+impl ShaderEntry {
+    /// Vertices de los anillos; vacio si el shader no tiene.
+    pub fn malla_anillos(&self) -> &[Vertex] {
+        &self.malla_anillos
+    }
+}
+
+/// Shaders disponibles por nombre, en orden de registro.
+#[derive(Default)]
+pub struct ShaderRegistry {
+    entries: Vec<ShaderEntry>,
+}
+
+impl ShaderRegistry {
+    /// Registro vacio.
+    pub fn new() -> Self {
+        ShaderRegistry { entries: Vec::new() }
+    }
+
+    /// Registra un shader. Si ya habia uno con el mismo nombre lo reemplaza
+    /// conservando su posicion.
+    pub fn register<S>(&mut self, name: &str, shader: S, noise: &str, defaults: ShaderDefaults)
+    where
+        S: FragmentShader + 'static,
+    {
+        let entry = ShaderEntry {
+            name: name.to_string(),
+            shader: Box::new(shader),
+            noise: noise.to_string(),
+            defaults,
+            malla_anillos: defaults.anillos.map_or_else(Vec::new, |anillos| {
+                mesh::anillo(anillos.radio_interior, anillos.radio_exterior, anillos.segmentos)
+            }),
+        };
+
+        match self.index_of(name) {
+            Some(index) => self.entries[index] = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Cantidad de shaders.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Indica si no hay shaders.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Los nombres en orden de registro.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    /// Posicion del shader `name`.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name == name)
+    }
+
+    /// El shader `name`.
+    pub fn get(&self, name: &str) -> Option<&ShaderEntry> {
+        self.index_of(name).map(|index| &self.entries[index])
+    }
+
+    /// El shader en la posicion `index`.
+    pub fn entry(&self, index: usize) -> Option<&ShaderEntry> {
+        self.entries.get(index)
+    }
+
+    /// Indice del shader siguiente, volviendo al primero despues del ultimo.
+    pub fn next(&self, index: usize) -> usize {
+        if self.entries.is_empty() {
+            return 0;
+        }
+        (index + 1) % self.entries.len()
+    }
+
+    /// Indice del shader anterior, pasando al ultimo antes del primero.
+    pub fn previous(&self, index: usize) -> usize {
+        if self.entries.is_empty() {
+            return 0;
+        }
+        (index + self.entries.len() - 1) % self.entries.len()
+    }
+}
+
+/// Los planetas de la demo y la estrella de `assets/escenas/sistema_solar.toml`.
+pub fn planetas() -> ShaderRegistry {
+    let mut registry = ShaderRegistry::new();
+    let defaults = ShaderDefaults::default();
+    let anillos = ShaderDefaults {
+        rotation: Vec3::new(PI / 4.0, 0.0, 0.0),
+        anillos: Some(Anillos::default()),
+        ..defaults
+    };
+
+    let con_atmosfera = |color: Color, grosor: f32, intensidad: f32| ShaderDefaults {
+        atmosfera: Some(AtmosferaShader { color, grosor, intensidad, ..AtmosferaShader::default() }),
+        ..defaults
+    };
+
+    registry.register("hielo", HieloShader::default(), "perlin", con_atmosfera(Color::new(200, 230, 255), 0.04, 0.5));
+    registry.register("volcanico", VolcanicoShader::default(), "cellular_puntas", defaults);
+    registry.register("oceano_profundo", OceanoProfundoShader::default(), "perlin", con_atmosfera(Color::new(90, 150, 255), 0.08, 0.9));
+    registry.register("desierto", DesiertoShader::default(), "grupos", con_atmosfera(Color::new(255, 190, 130), 0.05, 0.6));
+    registry.register("agua", AguaShader::default(), "perlin", con_atmosfera(Color::new(120, 180, 255), 0.08, 1.0));
+    registry.register("jungla", JunglaShader::default(), "cellular_bacteria", con_atmosfera(Color::new(140, 200, 255), 0.07, 0.9));
+    registry.register("metano", MetanoShader::default(), "camo", con_atmosfera(Color::new(120, 255, 230), 0.12, 1.0));
+    registry.register("rocoso_montanoso", RocosoMontanosoShader::default(), "cellular_agujero_negro", defaults);
+    registry.register("aurora", AuroraShader::default(), "variado", anillos);
+    registry.register("crateres", CrateresShader::default(), "perlin", defaults);
+    registry.register("lava_caliente", VolcanicoShader::lava_caliente(), "cellular_puntas", defaults);
+    registry.register("lava_fria", VolcanicoShader::lava_fria(), "cellular_puntas", defaults);
+    registry.register("estrella", EstrellaShader::default(), "noise_fbm", defaults);
+
+    registry
+}
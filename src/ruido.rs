@@ -0,0 +1,129 @@
+//! Biblioteca de generadores de ruido con nombre y los presets de la demo.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use noise::{OpenSimplex, Perlin, Simplex};
+use crate::noise_source::{DomainWarp, Fbm, NoiseRs, NoiseSource, Ridged};
+use crate::preset::{self, NoisePreset};
+use crate::toml_file::TomlError;
+
+/// Generadores de ruido ya configurados, por nombre. Se arman una sola vez y
+/// las `Uniforms` de cada cuadro solo los toman prestados.
+#[derive(Default)]
+pub struct NoiseLibrary {
+    presets: BTreeMap<String, Box<dyn NoiseSource>>,
+}
+
+impl NoiseLibrary {
+    /// Biblioteca vacia.
+    pub fn new() -> Self {
+        NoiseLibrary { presets: BTreeMap::new() }
+    }
+
+    /// Agrega un generador con un nombre; si ya habia uno con ese nombre lo
+    /// reemplaza.
+    pub fn insert<N: NoiseSource + 'static>(&mut self, name: &str, noise: N) {
+        self.presets.insert(name.to_string(), Box::new(noise));
+    }
+
+    /// El generador `name`.
+    pub fn get(&self, name: &str) -> Option<&dyn NoiseSource> {
+        self.presets.get(name).map(|noise| noise.as_ref())
+    }
+
+    /// Indica si hay un generador `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.presets.contains_key(name)
+    }
+
+    /// Carga todos los `*.toml` de `dir` (ver `preset::NoisePreset`); cada
+    /// archivo se registra con el nombre del archivo sin extension y reemplaza
+    /// al preset del mismo nombre si ya existia.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, TomlError> {
+        let entries = fs::read_dir(dir).map_err(|error| TomlError::Io(dir.to_path_buf(), error))?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|error| TomlError::Io(dir.to_path_buf(), error))?.path();
+            if path.extension().is_some_and(|extension| extension == "toml") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in &paths {
+            let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+            self.insert(name, preset::load(path)?);
+        }
+        Ok(paths.len())
+    }
+
+    /// Nombres de los presets en orden alfabetico.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(|name| name.as_str())
+    }
+}
+
+// Los presets de `fastnoise-lite` viven en `assets/ruido/` y se incluyen en
+// el binario para no depender del directorio de trabajo.
+const PRESETS: [(&str, &str); 7] = [
+    ("perlin", include_str!("../assets/ruido/perlin.toml")),
+    ("cellular_bacteria", include_str!("../assets/ruido/cellular_bacteria.toml")),
+    ("cellular_agujero_negro", include_str!("../assets/ruido/cellular_agujero_negro.toml")),
+    ("camo", include_str!("../assets/ruido/camo.toml")),
+    ("variado", include_str!("../assets/ruido/variado.toml")),
+    ("grupos", include_str!("../assets/ruido/grupos.toml")),
+    ("cellular_puntas", include_str!("../assets/ruido/cellular_puntas.toml")),
+];
+
+/// Los presets de ruido de la demo. Los que empiezan con `noise_` usan el
+/// crate `noise` en lugar de `fastnoise-lite`, para comparar calidad y costo
+/// con los mismos shaders.
+pub fn presets() -> NoiseLibrary {
+    presets_with_seed(None)
+}
+
+/// Los presets de `presets()`, todos con la semilla `seed` en lugar de la
+/// suya si se indica, para ver otra variante de los mismos planetas.
+pub fn presets_with_seed(seed: Option<u32>) -> NoiseLibrary {
+    let semilla = |propia: u32| seed.unwrap_or(propia);
+
+    let mut library = NoiseLibrary::new();
+    for (name, texto) in PRESETS {
+        let noise = NoisePreset::parse(texto)
+            .and_then(|mut preset| {
+                if let Some(seed) = seed {
+                    preset.seed = Some(seed as i32);
+                }
+                preset.build()
+            })
+            .unwrap_or_else(|error| panic!("assets/ruido/{}.toml: {}", name, error));
+        library.insert(name, noise);
+    }
+    library.insert("noise_simplex", crear_ruido_noise_simplex(semilla(100)));
+    library.insert("noise_fbm", crear_ruido_noise_fbm(semilla(100)));
+    library.insert("noise_ridged", crear_ruido_noise_ridged(semilla(1337)));
+    library.insert("noise_warp", crear_ruido_noise_warp(semilla(7)));
+    library
+}
+
+/// Simplex del crate `noise`.
+pub fn crear_ruido_noise_simplex(seed: u32) -> NoiseRs<Simplex> {
+    NoiseRs::new(Simplex::new(seed), 0.5)
+}
+
+/// fBm sobre Perlin del crate `noise`.
+pub fn crear_ruido_noise_fbm(seed: u32) -> Fbm<NoiseRs<Perlin>> {
+    Fbm::new(NoiseRs::new(Perlin::new(seed), 0.4), 5, 2.0, 0.5)
+}
+
+/// Ridged sobre OpenSimplex del crate `noise`.
+pub fn crear_ruido_noise_ridged(seed: u32) -> Ridged<NoiseRs<OpenSimplex>> {
+    Ridged::new(NoiseRs::new(OpenSimplex::new(seed), 0.3), 6, 2.0, 0.5)
+}
+
+/// El desplazamiento usa `seed + 4` para no repetir el ruido que desplaza.
+pub fn crear_ruido_noise_warp(seed: u32) -> DomainWarp<Fbm<NoiseRs<Simplex>>, NoiseRs<Perlin>> {
+    let fbm = Fbm::new(NoiseRs::new(Simplex::new(seed), 0.3), 4, 2.0, 0.5);
+    DomainWarp::new(fbm, NoiseRs::new(Perlin::new(seed.wrapping_add(4)), 0.2), 2.0)
+}
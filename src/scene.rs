@@ -0,0 +1,366 @@
+//! Escenas con varios objetos, orbitas y padres.
+
+use nalgebra_glm::{rotation, translation, Mat4, Vec3};
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+use std::fmt;
+use std::path::PathBuf;
+use crate::camera::Camera;
+use crate::lighting::Lighting;
+use crate::noise_source::NoiseSource;
+use crate::pipeline::create_model_matrix;
+use crate::registry::ShaderRegistry;
+use crate::ruido::NoiseLibrary;
+use crate::toml_file::TomlError;
+use crate::vertex::Vertex;
+
+/// Posicion, rotacion de Euler (x, luego y, luego z) y escala uniforme de un
+/// objeto en el mundo.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    /// Posicion; en un objeto con padre es relativa al padre.
+    pub translation: Vec3,
+    /// Rotacion de Euler en radianes.
+    pub rotation: Vec3,
+    /// Escala uniforme.
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Vec3::new(0.0, 0.0, 0.0),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    /// Matriz de modelo con esta transformacion.
+    pub fn model_matrix(&self) -> Mat4 {
+        create_model_matrix(self.translation, self.scale, self.rotation)
+    }
+}
+
+/// Orbita eliptica alrededor del origen del padre. El tiempo se mide en
+/// cuadros, como `Uniforms::time`.
+#[derive(Clone, Copy, Debug)]
+pub struct Orbit {
+    /// Semieje mayor de la elipse.
+    pub semi_major_axis: f32,
+    /// 0 es una circunferencia; debe ser menor que 1.
+    pub eccentricity: f32,
+    /// Cuadros que tarda una vuelta; 0 deja el objeto quieto en `phase`.
+    pub period: f32,
+    /// Angulo en radianes entre el plano de la orbita y el plano xz, girando
+    /// alrededor del eje x.
+    pub inclination: f32,
+    /// Angulo en radianes recorrido en el cuadro 0.
+    pub phase: f32,
+}
+
+impl Orbit {
+    /// Orbita circular de radio `radius` en el plano xz.
+    pub fn circular(radius: f32, period: f32, phase: f32) -> Self {
+        Orbit {
+            semi_major_axis: radius,
+            eccentricity: 0.0,
+            period,
+            inclination: 0.0,
+            phase,
+        }
+    }
+
+    /// Posicion relativa al padre en el instante `time`. El padre queda en un
+    /// foco de la elipse y el objeto avanza en sentido antihorario visto desde
+    /// +y, mas rapido cerca del padre (segunda ley de Kepler).
+    pub fn position(&self, time: f32) -> Vec3 {
+        let e = self.eccentricity.clamp(0.0, 0.99);
+        let vueltas = if self.period == 0.0 { 0.0 } else { time / self.period };
+        let anomalia_media = (self.phase + vueltas * 2.0 * PI) % (2.0 * PI);
+
+        // Ecuacion de Kepler, M = E - e sin E, por Newton.
+        let mut anomalia = anomalia_media;
+        for _ in 0..5 {
+            anomalia -= (anomalia - e * anomalia.sin() - anomalia_media) / (1.0 - e * anomalia.cos());
+        }
+
+        let a = self.semi_major_axis;
+        let b = a * (1.0 - e * e).sqrt();
+        let x = a * (anomalia.cos() - e);
+        let z = -b * anomalia.sin();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        Vec3::new(x, z * -sin_i, z * cos_i)
+    }
+}
+
+/// Un objeto de la escena. La malla, el shader y el ruido se nombran: la
+/// malla en `Scene`, el shader en un `ShaderRegistry` y el ruido en una
+/// `NoiseLibrary`.
+///
+/// `transform.translation` y la orbita son relativas al padre, si tiene; la
+/// rotacion, el giro y la escala son propios y no los heredan los hijos, asi
+/// una luna no gira con su planeta ni se aleja si el planeta es mas grande.
+#[derive(Clone, Debug)]
+pub struct SceneObject {
+    /// Nombre unico en la escena; lo usan los hijos en `parent`.
+    pub name: String,
+    /// Nombre de la malla en `Scene`.
+    pub mesh: String,
+    /// Nombre del shader en el `ShaderRegistry`.
+    pub shader: String,
+    /// Preset de ruido; `None` usa el registrado para el shader.
+    pub noise: Option<String>,
+    /// Transformacion propia del objeto.
+    pub transform: Transform,
+    /// Nombre del objeto alrededor del cual se mueve.
+    pub parent: Option<String>,
+    /// Orbita alrededor del padre, o del origen si no tiene.
+    pub orbit: Option<Orbit>,
+    /// Cuadros que tarda en girar sobre su eje y local (antes de aplicar
+    /// `transform.rotation`, que hace de inclinacion del eje); 0 no gira y los
+    /// valores negativos giran al reves.
+    pub spin_period: f32,
+}
+
+impl SceneObject {
+    /// Objeto sin padre, orbita ni giro.
+    pub fn new(name: &str, mesh: &str, shader: &str, transform: Transform) -> Self {
+        SceneObject {
+            name: name.to_string(),
+            mesh: mesh.to_string(),
+            shader: shader.to_string(),
+            noise: None,
+            transform,
+            parent: None,
+            orbit: None,
+            spin_period: 0.0,
+        }
+    }
+
+    /// Posicion relativa al padre en el instante `time`.
+    fn offset(&self, time: f32) -> Vec3 {
+        let orbita = self.orbit.map_or(Vec3::zeros(), |orbit| orbit.position(time));
+        self.transform.translation + orbita
+    }
+
+    fn spin(&self, time: f32) -> Mat4 {
+        if self.spin_period == 0.0 {
+            return Mat4::identity();
+        }
+        let angulo = (time / self.spin_period % 1.0) * 2.0 * PI;
+        rotation(angulo, &Vec3::new(0.0, 1.0, 0.0))
+    }
+}
+
+/// Error al leer o validar una escena. `Desconocido` y `Ciclo` salen de
+/// `Scene::validate`; los demas de `scene_file`, donde `Toml` tiene los errores
+/// de lectura y de campos, como `objects[2].orbit.period`.
+#[derive(Debug)]
+pub enum SceneError {
+    /// No se pudo leer el archivo, el TOML no es valido o tiene un valor
+    /// invalido.
+    Toml(TomlError),
+    /// No se pudo cargar una malla.
+    Malla {
+        /// La ruta ya resuelta desde el archivo de la escena.
+        path: PathBuf,
+        /// El error de `tobj`.
+        error: tobj::LoadError,
+    },
+    /// Un preset de `[noise]` invalido.
+    Ruido {
+        /// Nombre del preset.
+        nombre: String,
+        /// El error del preset.
+        error: TomlError,
+    },
+    /// Un error dentro de un archivo de escena.
+    Archivo {
+        /// El archivo de la escena.
+        path: PathBuf,
+        /// El error que tuvo.
+        error: Box<SceneError>,
+    },
+    /// Un objeto nombra algo que no existe.
+    Desconocido {
+        /// El objeto que lo nombra.
+        objeto: String,
+        /// "malla", "shader", "ruido" o "padre".
+        que: &'static str,
+        /// Lo que no existe.
+        nombre: String,
+    },
+    /// El objeto termina siendo su propio ancestro.
+    Ciclo {
+        /// El objeto del ciclo.
+        objeto: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Toml(error) => write!(f, "{}", error),
+            SceneError::Malla { path, error } => write!(f, "no se pudo cargar la malla {}: {}", path.display(), error),
+            SceneError::Ruido { nombre, error } => write!(f, "ruido `{}`: {}", nombre, error),
+            SceneError::Archivo { path, error } => write!(f, "{}: {}", path.display(), error),
+            SceneError::Desconocido { objeto, que, nombre } => {
+                write!(f, "{}: no existe el {} {}", objeto, que, nombre)
+            }
+            SceneError::Ciclo { objeto } => write!(f, "{}: la cadena de padres vuelve a este objeto", objeto),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<TomlError> for SceneError {
+    fn from(error: TomlError) -> Self {
+        SceneError::Toml(error)
+    }
+}
+
+/// Varios objetos que se dibujan en el mismo framebuffer y comparten el
+/// z-buffer, con la camara inicial, el fondo, las luces y la posicion del sol
+/// (la que separa el dia de la noche en los shaders).
+///
+/// `noise` tiene los presets de ruido propios de la escena; los objetos
+/// tambien pueden usar los de la `NoiseLibrary` que recibe
+/// `pipeline::render_scene`, y los de la escena tienen prioridad.
+pub struct Scene {
+    /// Camara con la que empieza el visor.
+    pub camera: Camera,
+    /// Color de fondo como `0xRRGGBB`.
+    pub background: u32,
+    /// Posicion del sol para los shaders.
+    pub sun_position: Vec3,
+    /// Luces de la escena.
+    pub lighting: Lighting,
+    /// Los objetos, en el orden en que se dibujan.
+    pub objects: Vec<SceneObject>,
+    /// Presets de ruido propios de la escena.
+    pub noise: NoiseLibrary,
+    meshes: BTreeMap<String, Vec<Vertex>>,
+}
+
+impl Scene {
+    /// Escena vacia iluminada por `Lighting::sun(sun_position)`.
+    pub fn new(camera: Camera, background: u32, sun_position: Vec3) -> Self {
+        Scene {
+            camera,
+            background,
+            sun_position,
+            lighting: Lighting::sun(sun_position),
+            objects: Vec::new(),
+            noise: NoiseLibrary::new(),
+            meshes: BTreeMap::new(),
+        }
+    }
+
+    /// Agrega una malla (vertices de tres en tres) con un nombre. Si ya habia
+    /// una con ese nombre la reemplaza.
+    pub fn insert_mesh(&mut self, name: &str, vertices: Vec<Vertex>) {
+        self.meshes.insert(name.to_string(), vertices);
+    }
+
+    /// La malla `name`.
+    pub fn mesh(&self, name: &str) -> Option<&[Vertex]> {
+        self.meshes.get(name).map(Vec::as_slice)
+    }
+
+    /// Busca un preset de ruido primero en la escena y despues en `library`.
+    pub fn noise<'a>(&'a self, name: &str, library: &'a NoiseLibrary) -> Option<&'a dyn NoiseSource> {
+        self.noise.get(name).or_else(|| library.get(name))
+    }
+
+    /// Agrega un objeto al final.
+    pub fn add(&mut self, object: SceneObject) {
+        self.objects.push(object);
+    }
+
+    /// Posicion del objeto `name` en `objects`.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.objects.iter().position(|object| object.name == name)
+    }
+
+    /// Matriz de modelo de cada objeto, en el mismo orden que `objects`, en el
+    /// instante `time` (en cuadros).
+    ///
+    /// Cada objeto se ubica en el marco de su padre: la traslacion acumulada
+    /// de sus ancestros mas la suya y su orbita. Un padre inexistente o un
+    /// ciclo se tratan como si el objeto no tuviera padre.
+    pub fn model_matrices(&self, time: u32) -> Vec<Mat4> {
+        let time = time as f32;
+        let mut marcos: Vec<Option<Mat4>> = vec![None; self.objects.len()];
+        for index in 0..self.objects.len() {
+            self.marco(index, time, &mut marcos, 0);
+        }
+
+        self.objects
+            .iter()
+            .zip(marcos)
+            .map(|(object, marco)| {
+                let marco = marco.unwrap_or_else(Mat4::identity);
+                let cuerpo = Transform { translation: Vec3::zeros(), ..object.transform };
+                marco * cuerpo.model_matrix() * object.spin(time)
+            })
+            .collect()
+    }
+
+    /// Traslacion del objeto `index` en el mundo, resolviendo primero la de
+    /// sus ancestros. `profundidad` corta los ciclos.
+    fn marco(&self, index: usize, time: f32, marcos: &mut [Option<Mat4>], profundidad: usize) -> Mat4 {
+        if let Some(marco) = marcos[index] {
+            return marco;
+        }
+
+        let object = &self.objects[index];
+        let padre = match object.parent.as_deref().and_then(|name| self.index_of(name)) {
+            Some(padre) if profundidad < self.objects.len() => self.marco(padre, time, marcos, profundidad + 1),
+            _ => Mat4::identity(),
+        };
+        let marco = padre * translation(&object.offset(time));
+        marcos[index] = Some(marco);
+        marco
+    }
+
+    /// Revisa que la malla, el shader, el ruido y el padre de cada objeto
+    /// existan y que no haya ciclos de padres. `pipeline::render_scene` se
+    /// salta los objetos con referencias que no existen.
+    pub fn validate(&self, registry: &ShaderRegistry, noise_library: &NoiseLibrary) -> Result<(), SceneError> {
+        for object in &self.objects {
+            let desconocido = |que, nombre: &str| SceneError::Desconocido {
+                objeto: object.name.clone(),
+                que,
+                nombre: nombre.to_string(),
+            };
+
+            if self.mesh(&object.mesh).is_none() {
+                return Err(desconocido("malla", &object.mesh));
+            }
+            let Some(entry) = registry.get(&object.shader) else {
+                return Err(desconocido("shader", &object.shader));
+            };
+            let noise = object.noise.as_deref().unwrap_or(&entry.noise);
+            if self.noise(noise, noise_library).is_none() {
+                return Err(desconocido("ruido", noise));
+            }
+
+            let mut ancestro = object.parent.as_deref();
+            let mut pasos = 0;
+            while let Some(name) = ancestro {
+                let Some(index) = self.index_of(name) else {
+                    return Err(desconocido("padre", name));
+                };
+                pasos += 1;
+                if name == object.name || pasos > self.objects.len() {
+                    return Err(SceneError::Ciclo { objeto: object.name.clone() });
+                }
+                ancestro = self.objects[index].parent.as_deref();
+            }
+        }
+        Ok(())
+    }
+}
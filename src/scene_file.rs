@@ -0,0 +1,377 @@
+//! Escenas descritas en archivos TOML y deteccion de cambios en el
+//! archivo.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::lighting::{Light, Lighting, SpecularModel};
+use crate::obj::Obj;
+use crate::preset::NoisePreset;
+use crate::scene::{Orbit, Scene, SceneError, SceneObject, Transform};
+use crate::toml_file::{self, campo, finito, no_negativo, positivo, TomlError};
+
+/// Una escena escrita en TOML, por ejemplo:
+///
+/// ```toml
+/// background = 0x05060d
+/// sun = [0.0, 0.0, 0.0]
+///
+/// [camera]
+/// eye = [0.0, 10.0, 26.0]
+///
+/// [meshes]
+/// esfera = "../sphere.obj"
+///
+/// [noise.granulado]
+/// type = "perlin"
+/// frequency = 0.5
+///
+/// [[lights]]
+/// type = "point"
+/// position = [0.0, 0.0, 0.0]
+///
+/// [[objects]]
+/// name = "estrella"
+/// mesh = "esfera"
+/// shader = "estrella"
+/// noise = "granulado"
+/// scale = 3.0
+///
+/// [[objects]]
+/// name = "oceano"
+/// mesh = "esfera"
+/// shader = "agua"
+/// parent = "estrella"
+/// spin_period = 200.0
+/// orbit = { semi_major_axis = 6.5, period = 1500.0 }
+/// ```
+///
+/// Las rutas de las mallas son relativas al archivo de la escena. Los
+/// shaders son los nombres de `registry::planetas`; el ruido puede ser un
+/// preset de `[noise]` (con el formato de `preset::NoisePreset`) o uno de
+/// `ruido::presets`. Los angulos van en radianes y los tiempos en cuadros.
+/// Sin `[[lights]]` la escena se ilumina con `Lighting::sun(sun)`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    /// Color de fondo como `0xRRGGBB`; negro si falta.
+    pub background: Option<u32>,
+    /// Posicion del sol; el origen si falta.
+    pub sun: Option<[f32; 3]>,
+    /// Luz ambiente en RGB.
+    pub ambient: Option<[u8; 3]>,
+    /// Modelo especular: `blinn_phong` (el valor por defecto) o `phong`.
+    pub specular: Option<String>,
+    /// Camara inicial; sin ella mira al origen desde (0, 0, 5).
+    pub camera: Option<CameraDescription>,
+    /// Mallas por nombre, con su ruta relativa al archivo.
+    #[serde(default)]
+    pub meshes: BTreeMap<String, String>,
+    /// Presets de ruido propios de la escena, por nombre.
+    #[serde(default)]
+    pub noise: BTreeMap<String, NoisePreset>,
+    /// Luces; sin ellas se usa `Lighting::sun(sun)`.
+    pub lights: Option<Vec<LightDescription>>,
+    /// Los objetos de la escena.
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+}
+
+/// La tabla `[camera]` de una escena.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDescription {
+    /// Posicion de la camara.
+    pub eye: [f32; 3],
+    /// Punto al que mira; el origen si falta.
+    pub center: Option<[f32; 3]>,
+    /// Direccion de arriba; +y si falta.
+    pub up: Option<[f32; 3]>,
+}
+
+/// Una entrada de `[[lights]]`.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LightDescription {
+    /// `point` o `directional`.
+    #[serde(rename = "type")]
+    pub light_type: String,
+    /// Posicion de una luz puntual.
+    pub position: Option<[f32; 3]>,
+    /// Direccion de una luz direccional; -z si falta.
+    pub direction: Option<[f32; 3]>,
+    /// Solo para luces puntuales; sin `range` no se atenuan.
+    pub range: Option<f32>,
+    /// Color en RGB; blanco si falta.
+    pub color: Option<[u8; 3]>,
+    /// Intensidad; 1 si falta.
+    pub intensity: Option<f32>,
+}
+
+/// Una entrada de `[[objects]]`; ver `scene::SceneObject`.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ObjectDescription {
+    /// Nombre unico en la escena.
+    pub name: String,
+    /// Nombre de una malla de `[meshes]`.
+    pub mesh: String,
+    /// Nombre de un shader de `registry::planetas`.
+    pub shader: String,
+    /// Preset de ruido; sin el se usa el registrado para el shader.
+    pub noise: Option<String>,
+    /// Posicion relativa al padre; el origen si falta.
+    pub translation: Option<[f32; 3]>,
+    /// Rotacion de Euler en radianes.
+    pub rotation: Option<[f32; 3]>,
+    /// Escala uniforme; 1 si falta.
+    pub scale: Option<f32>,
+    /// Nombre del objeto alrededor del cual se mueve.
+    pub parent: Option<String>,
+    /// Orbita alrededor del padre.
+    pub orbit: Option<OrbitDescription>,
+    /// Cuadros por vuelta sobre su eje; 0 o sin valor no gira.
+    pub spin_period: Option<f32>,
+}
+
+/// La orbita de un objeto; ver `scene::Orbit`.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct OrbitDescription {
+    /// Semieje mayor.
+    pub semi_major_axis: f32,
+    /// Excentricidad, de 0 a menos de 1; 0 si falta.
+    pub eccentricity: Option<f32>,
+    /// Cuadros por vuelta; 0 o sin valor deja el objeto quieto.
+    pub period: Option<f32>,
+    /// Inclinacion en radianes.
+    pub inclination: Option<f32>,
+    /// Angulo en el cuadro 0, en radianes.
+    pub phase: Option<f32>,
+}
+
+fn vec3(v: [f32; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+fn color(c: [u8; 3]) -> Color {
+    Color::new(c[0], c[1], c[2])
+}
+
+impl SceneDescription {
+    /// Lee una escena de TOML; los valores se revisan en `build`.
+    pub fn parse(texto: &str) -> Result<Self, SceneError> {
+        Ok(toml_file::parse(texto)?)
+    }
+
+    /// Valida la escena, carga sus mallas (relativas a `base`) y arma sus
+    /// presets de ruido. Que los shaders y el ruido de cada objeto existan se
+    /// revisa aparte con `Scene::validate`.
+    pub fn build(&self, base: &Path) -> Result<Scene, SceneError> {
+        let camera = match &self.camera {
+            Some(camera) => Camera::new(
+                vec3(camera.eye),
+                vec3(camera.center.unwrap_or([0.0, 0.0, 0.0])),
+                vec3(camera.up.unwrap_or([0.0, 1.0, 0.0])),
+            ),
+            None => Camera::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+        };
+        if camera.ojo == camera.centro {
+            return Err(campo("camera.eye", "no puede coincidir con camera.center").into());
+        }
+
+        let sun_position = vec3(self.sun.unwrap_or([0.0, 0.0, 0.0]));
+        let mut scene = Scene::new(camera, self.background.unwrap_or(0x000000), sun_position);
+        scene.lighting = self.lighting(sun_position)?;
+
+        for (name, ruta) in &self.meshes {
+            let path = base.join(ruta);
+            if !path.is_file() {
+                return Err(campo(format!("meshes.{}", name), format!("no existe el archivo {}", path.display())).into());
+            }
+            let obj = Obj::load(&path.to_string_lossy()).map_err(|error| SceneError::Malla { path, error })?;
+            scene.insert_mesh(name, obj.get_vertex_array());
+        }
+
+        for (name, preset) in &self.noise {
+            let noise = preset
+                .build()
+                .map_err(|error| SceneError::Ruido { nombre: name.clone(), error })?;
+            scene.noise.insert(name, noise);
+        }
+
+        for (index, object) in self.objects.iter().enumerate() {
+            if self.objects[..index].iter().any(|otro| otro.name == object.name) {
+                return Err(campo(format!("objects[{}].name", index), format!("\"{}\" esta repetido", object.name)).into());
+            }
+            scene.add(object.build(index)?);
+        }
+
+        Ok(scene)
+    }
+
+    fn lighting(&self, sun_position: Vec3) -> Result<Lighting, TomlError> {
+        let mut lighting = Lighting::sun(sun_position);
+        if let Some(ambient) = self.ambient {
+            lighting.ambient = color(ambient);
+        }
+        lighting.model = match self.specular.as_deref() {
+            None | Some("blinn_phong") => SpecularModel::BlinnPhong,
+            Some("phong") => SpecularModel::Phong,
+            Some(otro) => {
+                return Err(campo("specular", format!("valor desconocido \"{}\" (usar phong, blinn_phong)", otro)));
+            }
+        };
+
+        if let Some(lights) = &self.lights {
+            lighting.lights = lights
+                .iter()
+                .enumerate()
+                .map(|(index, light)| light.build(index))
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(lighting)
+    }
+}
+
+impl LightDescription {
+    fn build(&self, index: usize) -> Result<Light, TomlError> {
+        let prefijo = format!("lights[{}]", index);
+        let color = color(self.color.unwrap_or([255, 255, 255]));
+        let intensity = no_negativo(&format!("{}.intensity", prefijo), self.intensity.unwrap_or(1.0))?;
+
+        match self.light_type.as_str() {
+            "point" => {
+                if self.direction.is_some() {
+                    return Err(campo(format!("{}.direction", prefijo), "solo se usa con type = \"directional\""));
+                }
+                let Some(position) = self.position else {
+                    return Err(campo(format!("{}.position", prefijo), "es obligatorio con type = \"point\""));
+                };
+                let range = match self.range {
+                    Some(range) => positivo(&format!("{}.range", prefijo), range)?,
+                    None => f32::INFINITY,
+                };
+                Ok(Light::point(vec3(position), range, color, intensity))
+            }
+            "directional" => {
+                for (nombre, presente) in [("position", self.position.is_some()), ("range", self.range.is_some())] {
+                    if presente {
+                        return Err(campo(format!("{}.{}", prefijo, nombre), "solo se usa con type = \"point\""));
+                    }
+                }
+                let direction = vec3(self.direction.unwrap_or([0.0, 0.0, -1.0]));
+                if direction.magnitude() == 0.0 {
+                    return Err(campo(format!("{}.direction", prefijo), "no puede ser cero"));
+                }
+                Ok(Light::directional(direction, color, intensity))
+            }
+            otro => Err(campo(
+                format!("{}.type", prefijo),
+                format!("valor desconocido \"{}\" (usar point, directional)", otro),
+            )),
+        }
+    }
+}
+
+impl ObjectDescription {
+    fn build(&self, index: usize) -> Result<SceneObject, TomlError> {
+        let prefijo = format!("objects[{}]", index);
+        let transform = Transform {
+            translation: vec3(self.translation.unwrap_or([0.0, 0.0, 0.0])),
+            rotation: vec3(self.rotation.unwrap_or([0.0, 0.0, 0.0])),
+            scale: positivo(&format!("{}.scale", prefijo), self.scale.unwrap_or(1.0))?,
+        };
+
+        let mut object = SceneObject::new(&self.name, &self.mesh, &self.shader, transform);
+        object.noise = self.noise.clone();
+        object.parent = self.parent.clone();
+        object.spin_period = finito(&format!("{}.spin_period", prefijo), self.spin_period.unwrap_or(0.0))?;
+        if let Some(orbit) = &self.orbit {
+            object.orbit = Some(orbit.build(&format!("{}.orbit", prefijo))?);
+        }
+        Ok(object)
+    }
+}
+
+impl OrbitDescription {
+    fn build(&self, prefijo: &str) -> Result<Orbit, TomlError> {
+        let eccentricity = self.eccentricity.unwrap_or(0.0);
+        if !(0.0..1.0).contains(&eccentricity) {
+            return Err(campo(
+                format!("{}.eccentricity", prefijo),
+                format!("debe estar entre 0 y 1 (sin incluir el 1), no {}", eccentricity),
+            ));
+        }
+
+        Ok(Orbit {
+            semi_major_axis: no_negativo(&format!("{}.semi_major_axis", prefijo), self.semi_major_axis)?,
+            eccentricity,
+            period: finito(&format!("{}.period", prefijo), self.period.unwrap_or(0.0))?,
+            inclination: finito(&format!("{}.inclination", prefijo), self.inclination.unwrap_or(0.0))?,
+            phase: finito(&format!("{}.phase", prefijo), self.phase.unwrap_or(0.0))?,
+        })
+    }
+}
+
+/// Lee una escena desde un archivo; las mallas se buscan junto a el.
+pub fn load(path: &Path) -> Result<Scene, SceneError> {
+    let texto = toml_file::leer(path)?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    SceneDescription::parse(&texto)
+        .and_then(|description| description.build(base))
+        .map_err(|error| SceneError::Archivo { path: path.to_path_buf(), error: Box::new(error) })
+}
+
+/// Avisa cuando cambia la fecha de modificacion de un archivo. Se consulta en
+/// cada cuadro pero mira el disco como mucho cada `intervalo`.
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    ultimo_chequeo: Instant,
+    intervalo: Duration,
+}
+
+impl FileWatcher {
+    /// Vigila `path` desde su fecha de modificacion actual.
+    pub fn new(path: &Path) -> Self {
+        FileWatcher {
+            path: path.to_path_buf(),
+            modified: Self::modified(path),
+            ultimo_chequeo: Instant::now(),
+            intervalo: Duration::from_millis(250),
+        }
+    }
+
+    /// El archivo vigilado.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// `true` si el archivo cambio desde la ultima vez que devolvio `true` (o
+    /// desde que se creo el `FileWatcher`). Mientras el archivo no existe, por
+    /// ejemplo en medio de un guardado, no avisa.
+    pub fn changed(&mut self) -> bool {
+        if self.ultimo_chequeo.elapsed() < self.intervalo {
+            return false;
+        }
+        self.ultimo_chequeo = Instant::now();
+
+        match Self::modified(&self.path) {
+            Some(modified) if Some(modified) != self.modified => {
+                self.modified = Some(modified);
+                true
+            }
+            _ => false,
+        }
+    }
+}
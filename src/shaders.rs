@@ -1,12 +1,19 @@
+//! El vertex shader y los fragment shaders de los planetas, atmosferas,
+//! anillos y la estrella.
 
 use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
 use crate::vertex::Vertex;
-use crate::Uniforms;
+use crate::pipeline::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
+use crate::lighting::Material;
 
-use crate::FastNoiseLite;
+use noise::Simplex;
+use crate::noise_source::{Fbm, NoiseRs, NoiseSource};
 
+/// Transforma un vertice a clip space y a espacio de mundo, y su normal con la
+/// matriz de normales del modelo. La division perspectiva se hace despues del
+/// recorte.
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     let position = Vec4::new(
         vertex.position.x,
@@ -15,17 +22,8 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         1.0
     );
 
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
-
-    let w = transformed.w;
-    let transformed_position = Vec4::new(
-        transformed.x / w,
-        transformed.y / w,
-        transformed.z / w,
-        1.0
-    );
-
-    let screen_position = uniforms.viewport_matrix * transformed_position;
+    let world_position = uniforms.model_matrix * position;
+    let clip_position = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
 
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
     let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
@@ -37,38 +35,114 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         normal: vertex.normal,
         tex_coords: vertex.tex_coords,
         color: vertex.color,
-        transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
-        transformed_normal: transformed_normal
+        clip_position,
+        world_position: world_position.xyz(),
+        transformed_position: Vec3::zeros(),
+        transformed_normal,
+        inv_w: 1.0,
+    }
+}
+
+/// Aplica las luces de `uniforms` al color de un fragmento, con su posicion y
+/// normal en espacio de mundo.
+pub fn iluminar(color: Color, material: &Material, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    uniforms.lighting.shade(color, material, &fragment.world_position, &fragment.normal, &uniforms.camera_position)
+}
+
+/// Un fragment shader: calcula el color de un fragmento visible.
+///
+/// Los shaders de planetas son structs con sus colores y parametros como
+/// campos publicos, asi se pueden armar variantes del mismo planeta. Cualquier
+/// `fn(&Fragment, &Uniforms) -> Color` tambien sirve como shader.
+///
+/// En los que tienen `escala` y `animacion`, esos campos van directo a
+/// `ruido_superficie`: `escala` es la frecuencia del ruido sobre la esfera y
+/// `animacion` la velocidad con la que cambia por cuadro (0 lo deja fijo).
+pub trait FragmentShader: Sync {
+    /// Color del fragmento ya iluminado.
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color;
+
+    /// Color y opacidad en [0, 1]. Los shaders opacos no necesitan
+    /// implementarlo; la opacidad solo se usa con `BlendMode::Alpha` y
+    /// `BlendMode::Additive`.
+    fn shade_alpha(&self, fragment: &Fragment, uniforms: &Uniforms) -> (Color, f32) {
+        (self.shade(fragment, uniforms), 1.0)
+    }
+}
+
+impl<F> FragmentShader for F
+where
+    F: Fn(&Fragment, &Uniforms) -> Color + Sync,
+{
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self(fragment, uniforms)
     }
 }
 
- 
+/// Oceano con olas animadas a partir de ruido fractal.
+pub struct AguaShader {
+    /// Color del agua en los valles de las olas.
+    pub agua_1: Color,
+    /// Color del agua en las crestas.
+    pub agua_2: Color,
+    /// Espuma: se mezcla donde el ruido es mas fuerte en valor absoluto.
+    pub color_h: Color,
+    /// Desplazamiento del ruido por cuadro.
+    pub velocidad: f32,
+    /// Capas de ruido que se suman en `ruido_fractal`.
+    pub octavas: u32,
+    /// Factor con el que crece la frecuencia en cada octava.
+    pub lacunaridad: f32,
+    /// Factor con el que baja la amplitud en cada octava.
+    pub ganancia: f32,
+    /// Con brillo especular para que el sol se refleje en el agua.
+    pub material: Material,
+}
 
-pub fn shader_agua(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let agua_1 = Color::new(0, 105, 148);  
-    let agua_2 = Color::new(0, 191, 255);  
-    let color_h = Color::new(173, 216, 230);  
+impl Default for AguaShader {
+    fn default() -> Self {
+        AguaShader {
+            agua_1: Color::new(0, 105, 148),
+            agua_2: Color::new(0, 191, 255),
+            color_h: Color::new(173, 216, 230),
+            velocidad: 0.02,
+            octavas: 5,
+            lacunaridad: 2.0,
+            ganancia: 0.5,
+            material: Material {
+                ambient: 0.05,
+                specular: 0.6,
+                shininess: 48.0,
+                ..Material::default()
+            },
+        }
+    }
+}
 
-    let position = fragment.vertex_position;
-    let t = uniforms.time as f32 * 0.02;  
+impl FragmentShader for AguaShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let t = uniforms.time as f32 * self.velocidad;
+        let position = fragment.vertex_position.add_scalar(t);
 
-    let ruido = ruido_fractal(&uniforms.noise, position.x + t, position.y + t, 5, 2.0, 0.5);
+        let ruido = ruido_fractal(uniforms.noise, position, self.octavas, self.lacunaridad, self.ganancia);
 
-    let olas = (1.0 + ruido) * 0.5; 
-    let base_color = agua_1.lerp(&agua_2, olas);
-    let final_color = base_color.lerp(&color_h, ruido.abs());
+        let olas = (1.0 + ruido) * 0.5;
+        let base_color = self.agua_1.lerp(&self.agua_2, olas);
+        let final_color = base_color.lerp(&self.color_h, ruido.abs());
 
-    final_color * fragment.intensity
+        iluminar(final_color, &self.material, fragment, uniforms)
+    }
 }
 
-fn ruido_fractal(noise: &FastNoiseLite, x: f32, y: f32, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+fn ruido_fractal(noise: &dyn NoiseSource, position: Vec3, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
     let mut total = 10.0;
     let mut frequency = 20.0;
     let mut amplitude = 30.0;
     let mut max_value = 0.0; 
 
     for _ in 0..octaves {
-        total += noise.get_noise_2d(x * frequency, y * frequency) * amplitude;
+        let p = position * frequency;
+        total += noise.sample_3d(p.x, p.y, p.z) * amplitude;
         max_value += amplitude;
 
         amplitude *= gain;
@@ -77,78 +151,585 @@ fn ruido_fractal(noise: &FastNoiseLite, x: f32, y: f32, octaves: u32, lacunarity
 
     total / max_value 
 }
- 
-pub fn volcanico_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let color_roca = Color::new(169, 169, 169);
-    let color_magma = Color::new(255, 69, 0);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 10.0, fragment.vertex_position.y * 10.0);
-    let factor = ((ruido + 1.0) / 2.0).powf(3.0);
-    color_roca.lerp(&color_magma, factor) * fragment.intensity
+
+// Desplazamiento entre cortes sucesivos del ruido animado; lo bastante grande
+// para que los cortes no se parezcan entre si.
+const PASO_ANIMACION: Vec3 = Vec3::new(31.7, 47.3, 19.1);
+
+/// Ruido 3D sobre la posicion del fragmento en el espacio del objeto, asi cada
+/// punto de la esfera tiene su propio valor y no hay costuras ni patrones
+/// espejados entre el frente y la parte de atras.
+///
+/// Con `animacion` > 0 se aproxima un ruido 4D: se interpola suavemente entre
+/// dos cortes 3D desplazados que avanzan con el tiempo.
+pub fn ruido_superficie(noise: &dyn NoiseSource, position: &Vec3, escala: f32, animacion: f32, time: u32) -> f32 {
+    let p = position * escala;
+    if animacion == 0.0 {
+        return noise.sample_3d(p.x, p.y, p.z);
+    }
+
+    let t = time as f32 * animacion;
+    let corte = t.floor();
+    let f = t - corte;
+    let f = f * f * (3.0 - 2.0 * f);
+
+    let a = p + PASO_ANIMACION * corte;
+    let b = a + PASO_ANIMACION;
+    let ruido_a = noise.sample_3d(a.x, a.y, a.z);
+    let ruido_b = noise.sample_3d(b.x, b.y, b.z);
+    ruido_a + (ruido_b - ruido_a) * f
+}
+
+/// Brillo de la atmosfera en el borde del planeta. Se dibuja sobre una esfera
+/// un poco mas grande que el planeta (`grosor` es la fraccion de radio que se
+/// agrega) con mezcla aditiva y sin escribir profundidad.
+///
+/// El brillo crece hacia el borde con un termino de Fresnel,
+/// `(1 - |n . v|)^potencia`, y se apaga en el lado nocturno.
+#[derive(Clone, Copy, Debug)]
+pub struct AtmosferaShader {
+    /// Color del brillo; la opacidad la pone el termino de Fresnel.
+    pub color: Color,
+    /// Fraccion del radio del planeta que se agrega a la esfera de la atmosfera.
+    pub grosor: f32,
+    /// Multiplica la opacidad antes de recortarla a [0, 1].
+    pub intensidad: f32,
+    /// Exponente de Fresnel: valores altos concentran el brillo en el borde.
+    pub potencia: f32,
+}
+
+impl Default for AtmosferaShader {
+    fn default() -> Self {
+        AtmosferaShader {
+            color: Color::new(120, 180, 255),
+            grosor: 0.08,
+            intensidad: 1.0,
+            potencia: 3.0,
+        }
+    }
+}
+
+impl FragmentShader for AtmosferaShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.shade_alpha(fragment, uniforms).0
+    }
+
+    fn shade_alpha(&self, fragment: &Fragment, uniforms: &Uniforms) -> (Color, f32) {
+        let vista = (uniforms.camera_position - fragment.world_position).normalize();
+        let fresnel = (1.0 - fragment.normal.dot(&vista).abs()).powf(self.potencia);
+
+        let al_sol = (uniforms.sun_position - fragment.world_position).normalize();
+        let dia = smoothstep(-0.3, 0.4, fragment.normal.dot(&al_sol));
+
+        (self.color, (fresnel * dia * self.intensidad).clamp(0.0, 1.0))
+    }
+}
+
+/// Anillos planetarios semitransparentes para la malla de `mesh::anillo`.
+///
+/// La posicion radial sale de `tex_coords.x` (0 en el borde interior, 1 en el
+/// exterior). El color pasa de `interior` a `exterior`, las bandas combinan una
+/// onda de frecuencia `bandas` con ruido, y la opacidad cae a cero en la
+/// division principal y donde el ruido radial baja de `huecos`.
+///
+/// Como `LucesNocturnas`, el ruido radial es propio y no el preset del
+/// planeta, asi los huecos no cambian al cambiar el ruido del planeta.
+#[derive(Clone, Copy, Debug)]
+pub struct AnillosShader {
+    /// Color en el borde interior.
+    pub interior: Color,
+    /// Color en el borde exterior.
+    pub exterior: Color,
+    /// Ruido radial de las bandas y los huecos.
+    pub ruido: Fbm<NoiseRs<Simplex>>,
+    /// Cantidad de bandas de la onda a lo ancho del anillo.
+    pub bandas: f32,
+    /// Umbral del ruido radial por debajo del cual hay un hueco; 0 no deja
+    /// huecos y valores cercanos a 1 dejan pocos anillos.
+    pub huecos: f32,
+    /// Posicion radial (en [0, 1]) de la division principal.
+    pub division: f32,
+    /// Ancho de la division principal en la misma unidad radial.
+    pub ancho_division: f32,
+    /// Opacidad maxima de las bandas.
+    pub opacidad: f32,
+}
+
+impl Default for AnillosShader {
+    fn default() -> Self {
+        AnillosShader {
+            interior: Color::new(150, 130, 110),
+            exterior: Color::new(225, 205, 170),
+            ruido: Fbm::new(NoiseRs::new(Simplex::new(1610), 12.0), 4, 2.0, 0.5),
+            bandas: 40.0,
+            huecos: 0.3,
+            division: 0.62,
+            ancho_division: 0.05,
+            opacidad: 0.85,
+        }
+    }
+}
+
+impl FragmentShader for AnillosShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.shade_alpha(fragment, uniforms).0
+    }
+
+    fn shade_alpha(&self, fragment: &Fragment, uniforms: &Uniforms) -> (Color, f32) {
+        let radial = fragment.tex_coords.x;
+
+        let ruido = self.ruido.sample_2d(radial, 0.0) * 0.5 + 0.5;
+        let onda = (radial * self.bandas * 2.0 * std::f32::consts::PI).sin() * 0.5 + 0.5;
+        let banda = onda * 0.4 + ruido * 0.6;
+
+        let division = smoothstep(0.0, self.ancho_division, (radial - self.division).abs());
+        let hueco = smoothstep(self.huecos - 0.05, self.huecos + 0.05, ruido);
+        let borde = smoothstep(0.0, 0.03, radial) * smoothstep(0.0, 0.03, 1.0 - radial);
+        let alpha = self.opacidad * (0.5 + 0.5 * banda) * division * hueco * borde;
+
+        // El anillo no tiene cara de atras: se ilumina la que mira al sol.
+        let al_sol = uniforms.sun_position - fragment.world_position;
+        let normal = if fragment.normal.dot(&al_sol) < 0.0 { -fragment.normal } else { fragment.normal };
+        let material = Material { ambient: 0.3, ..Material::default() };
+        let color = self.interior.lerp(&self.exterior, radial).lerp(&Color::black(), (1.0 - banda) * 0.3);
+        let color = uniforms.lighting.shade(color, &material, &fragment.world_position, &normal, &uniforms.camera_position);
+
+        (color, alpha)
+    }
+}
+
+/// Capa emisiva del lado nocturno de un planeta habitado: luces de ciudades
+/// que aparecen donde el ruido supera `umbral` y se encienden a lo largo de
+/// una franja suave alrededor del terminador.
+///
+/// Las ciudades usan su propio ruido y no el preset del planeta, asi se ven
+/// parecidas en cualquier planeta.
+pub struct LucesNocturnas {
+    /// Color de las luces; se suma al del lado de dia.
+    pub color: Color,
+    /// Ruido propio de las ciudades, independiente del preset del planeta.
+    pub ruido: Fbm<NoiseRs<Simplex>>,
+    /// Escala del ruido de las ciudades; mas alta da ciudades mas chicas.
+    pub escala: f32,
+    /// Valor del ruido (en [0, 1]) a partir del cual hay ciudad.
+    pub umbral: f32,
+    /// Medio ancho de la franja del terminador, en coseno del angulo con el sol.
+    pub terminador: f32,
+}
+
+impl Default for LucesNocturnas {
+    fn default() -> Self {
+        LucesNocturnas {
+            color: Color::new(255, 196, 110),
+            ruido: Fbm::new(NoiseRs::new(Simplex::new(2024), 1.0), 4, 2.0, 0.5),
+            escala: 6.0,
+            umbral: 0.6,
+            terminador: 0.15,
+        }
+    }
 }
 
-pub fn oceano_profundo_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let color_superficie = Color::new(0, 105, 148);
-    let color_profundidad = Color::new(0, 34, 102);
-    let profundidad = (fragment.vertex_position.y * 5.0).sin() * 0.5 + 0.5;
-    color_superficie.lerp(&color_profundidad, profundidad) * fragment.intensity
+impl LucesNocturnas {
+    /// Suma las luces de las ciudades a `dia`, el color ya iluminado, segun
+    /// que tan de noche es el fragmento respecto a `uniforms.sun_position`.
+    pub fn aplicar(&self, dia: Color, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let al_sol = (uniforms.sun_position - fragment.world_position).normalize();
+        let coseno = fragment.normal.dot(&al_sol);
+        let noche = 1.0 - smoothstep(-self.terminador, self.terminador, coseno);
+        if noche <= 0.0 {
+            return dia;
+        }
+
+        let p = fragment.vertex_position * self.escala;
+        let ruido = self.ruido.sample_3d(p.x, p.y, p.z);
+        let ciudad = smoothstep(self.umbral, self.umbral + 0.1, ruido * 0.5 + 0.5);
+        dia + self.color * (ciudad * noche)
+    }
 }
 
-pub fn desierto_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let arena_clara = Color::new(237, 201, 175);
-    let arena_oscura = Color::new(210, 180, 140);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 5.0, fragment.vertex_position.y * 5.0);
-    let factor = (ruido * 0.5 + 0.5).powf(2.0);
-    arena_clara.lerp(&arena_oscura, factor) * fragment.intensity
+fn smoothstep(borde_0: f32, borde_1: f32, x: f32) -> f32 {
+    let t = ((x - borde_0) / (borde_1 - borde_0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
 }
 
-pub fn hielo_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let hielo = Color::new(173, 216, 230);
-    let grieta = Color::new(224, 255, 255);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 15.0, fragment.vertex_position.y * 15.0);
-    let factor = (ruido * ruido).clamp(0.0, 1.0);
-    hielo.lerp(&grieta, factor) * fragment.intensity
+/// Roca con vetas de magma; `exponente` controla que tan escaso es el magma.
+pub struct VolcanicoShader {
+    /// Color de la roca donde no hay magma.
+    pub roca: Color,
+    /// Color de las vetas de magma.
+    pub magma: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Valores altos dejan menos magma.
+    pub exponente: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
 }
 
-pub fn jungla_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let verde_oscuro = Color::new(34, 139, 34);
-    let verde_claro = Color::new(50, 205, 50);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 20.0, fragment.vertex_position.y * 20.0);
-    let factor = ((ruido + 1.0) / 2.0).powf(1.5);
-    verde_oscuro.lerp(&verde_claro, factor) * fragment.intensity
+impl VolcanicoShader {
+    /// Mundo de lava joven: magma brillante que cubre buena parte de la roca y
+    /// se mueve con el tiempo.
+    pub fn lava_caliente() -> Self {
+        VolcanicoShader {
+            roca: Color::new(90, 40, 30),
+            magma: Color::new(255, 120, 0),
+            escala: 10.0,
+            exponente: 1.5,
+            animacion: 0.02,
+        }
+    }
+
+    /// Mundo de lava casi apagado: roca fria con pocas grietas rojas.
+    pub fn lava_fria() -> Self {
+        VolcanicoShader {
+            roca: Color::new(60, 60, 70),
+            magma: Color::new(180, 30, 0),
+            escala: 14.0,
+            exponente: 5.0,
+            animacion: 0.0,
+        }
+    }
 }
 
-pub fn metano_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let azul_gas = Color::new(0, 153, 204);
-    let azul_oscuro = Color::new(0, 51, 102);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x, fragment.vertex_position.y);
-    let factor = (ruido * 0.5 + 0.5).powf(3.0);
-    azul_gas.lerp(&azul_oscuro, factor) * fragment.intensity
+impl Default for VolcanicoShader {
+    fn default() -> Self {
+        VolcanicoShader {
+            roca: Color::new(169, 169, 169),
+            magma: Color::new(255, 69, 0),
+            escala: 10.0,
+            exponente: 3.0,
+            animacion: 0.0,
+        }
+    }
 }
 
-pub fn rocoso_montanoso_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let roca = Color::new(139, 69, 19);
-    let nieve = Color::new(255, 250, 250);
-    let altura = (fragment.vertex_position.y * 3.0).sin() * 0.5 + 0.5;
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 10.0, fragment.vertex_position.y * 10.0);
-    let factor = ((ruido + 1.0) / 2.0) * altura;
-    roca.lerp(&nieve, factor) * fragment.intensity
+impl FragmentShader for VolcanicoShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = ((ruido + 1.0) / 2.0).powf(self.exponente);
+        iluminar(self.roca.lerp(&self.magma, factor), &Material::default(), fragment, uniforms)
+    }
 }
 
-pub fn aurora_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let verde = Color::new(0, 255, 127);
-    let morado = Color::new(75, 0, 130);
-    let amarillo = Color::new(255, 255, 0);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 5.0, fragment.vertex_position.y * 5.0);
-    let factor = ((ruido + 1.0) / 2.0).powf(2.0);
-    verde.lerp(&morado, factor).lerp(&amarillo, factor) * fragment.intensity
+/// Bandas de profundidad segun la latitud, sin ruido.
+pub struct OceanoProfundoShader {
+    /// Color de las bandas poco profundas.
+    pub superficie: Color,
+    /// Color de las bandas profundas.
+    pub profundidad: Color,
+    /// Frecuencia de las bandas a lo largo de y.
+    pub frecuencia: f32,
 }
 
-pub fn crateres_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let gris_oscuro = Color::new(169, 169, 169);
-    let gris_claro = Color::new(211, 211, 211);
-    let ruido = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 15.0, fragment.vertex_position.y * 15.0);
-    let factor = (ruido * ruido).powf(1.5);
-    gris_oscuro.lerp(&gris_claro, factor) * fragment.intensity
+impl Default for OceanoProfundoShader {
+    fn default() -> Self {
+        OceanoProfundoShader {
+            superficie: Color::new(0, 105, 148),
+            profundidad: Color::new(0, 34, 102),
+            frecuencia: 5.0,
+        }
+    }
 }
 
+impl FragmentShader for OceanoProfundoShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let profundidad = (fragment.vertex_position.y * self.frecuencia).sin() * 0.5 + 0.5;
+        iluminar(self.superficie.lerp(&self.profundidad, profundidad), &Material::default(), fragment, uniforms)
+    }
+}
 
+/// Dunas de arena a partir de ruido, con luces de ciudades de noche.
+pub struct DesiertoShader {
+    /// Color de la arena en las zonas bajas del ruido.
+    pub arena_clara: Color,
+    /// Color de la arena en las dunas.
+    pub arena_oscura: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Valores altos dejan menos arena oscura.
+    pub exponente: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+    /// Luces de ciudades del lado nocturno, si el planeta las tiene.
+    pub noche: Option<LucesNocturnas>,
+}
+
+impl Default for DesiertoShader {
+    fn default() -> Self {
+        DesiertoShader {
+            arena_clara: Color::new(237, 201, 175),
+            arena_oscura: Color::new(210, 180, 140),
+            escala: 5.0,
+            exponente: 2.0,
+            animacion: 0.0,
+            noche: Some(LucesNocturnas {
+                escala: 4.0,
+                umbral: 0.58,
+                ..LucesNocturnas::default()
+            }),
+        }
+    }
+}
+
+impl FragmentShader for DesiertoShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = (ruido * 0.5 + 0.5).powf(self.exponente);
+        let dia = iluminar(self.arena_clara.lerp(&self.arena_oscura, factor), &Material::default(), fragment, uniforms);
+        match &self.noche {
+            Some(noche) => noche.aplicar(dia, fragment, uniforms),
+            None => dia,
+        }
+    }
+}
+
+/// Hielo con grietas claras donde el ruido se aleja de cero.
+pub struct HieloShader {
+    /// Color de la capa de hielo.
+    pub hielo: Color,
+    /// Color de las grietas, donde el ruido se aleja de cero.
+    pub grieta: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+}
+
+impl Default for HieloShader {
+    fn default() -> Self {
+        HieloShader {
+            hielo: Color::new(173, 216, 230),
+            grieta: Color::new(224, 255, 255),
+            escala: 15.0,
+            animacion: 0.0,
+        }
+    }
+}
+
+impl FragmentShader for HieloShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = (ruido * ruido).clamp(0.0, 1.0);
+        iluminar(self.hielo.lerp(&self.grieta, factor), &Material::default(), fragment, uniforms)
+    }
+}
+
+/// Vegetacion con manchas de ruido y luces de ciudades de noche.
+pub struct JunglaShader {
+    /// Color de la selva cerrada.
+    pub verde_oscuro: Color,
+    /// Color de los claros.
+    pub verde_claro: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Valores altos dejan menos claros.
+    pub exponente: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+    /// Luces de ciudades del lado nocturno, si el planeta las tiene.
+    pub noche: Option<LucesNocturnas>,
+}
+
+impl Default for JunglaShader {
+    fn default() -> Self {
+        JunglaShader {
+            verde_oscuro: Color::new(34, 139, 34),
+            verde_claro: Color::new(50, 205, 50),
+            escala: 20.0,
+            exponente: 1.5,
+            animacion: 0.0,
+            noche: Some(LucesNocturnas::default()),
+        }
+    }
+}
+
+impl FragmentShader for JunglaShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = ((ruido + 1.0) / 2.0).powf(self.exponente);
+        let dia = iluminar(self.verde_oscuro.lerp(&self.verde_claro, factor), &Material::default(), fragment, uniforms);
+        match &self.noche {
+            Some(noche) => noche.aplicar(dia, fragment, uniforms),
+            None => dia,
+        }
+    }
+}
+
+/// Gigante de metano con nubes de ruido.
+pub struct MetanoShader {
+    /// Color del gas en las zonas claras.
+    pub azul_gas: Color,
+    /// Color de las nubes densas.
+    pub azul_oscuro: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Valores altos dejan menos nubes densas.
+    pub exponente: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+}
+
+impl Default for MetanoShader {
+    fn default() -> Self {
+        MetanoShader {
+            azul_gas: Color::new(0, 153, 204),
+            azul_oscuro: Color::new(0, 51, 102),
+            escala: 1.0,
+            exponente: 3.0,
+            animacion: 0.0,
+        }
+    }
+}
+
+impl FragmentShader for MetanoShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = (ruido * 0.5 + 0.5).powf(self.exponente);
+        iluminar(self.azul_gas.lerp(&self.azul_oscuro, factor), &Material::default(), fragment, uniforms)
+    }
+}
+
+/// Roca con nieve en las bandas altas.
+pub struct RocosoMontanosoShader {
+    /// Color de la roca.
+    pub roca: Color,
+    /// Color de la nieve en las bandas altas.
+    pub nieve: Color,
+    /// Frecuencia de las bandas de altura a lo largo de y.
+    pub frecuencia_altura: f32,
+    /// Frecuencia del ruido de la nieve.
+    pub escala: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+}
+
+impl Default for RocosoMontanosoShader {
+    fn default() -> Self {
+        RocosoMontanosoShader {
+            roca: Color::new(139, 69, 19),
+            nieve: Color::new(255, 250, 250),
+            frecuencia_altura: 3.0,
+            escala: 10.0,
+            animacion: 0.0,
+        }
+    }
+}
+
+impl FragmentShader for RocosoMontanosoShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let altura = (fragment.vertex_position.y * self.frecuencia_altura).sin() * 0.5 + 0.5;
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = ((ruido + 1.0) / 2.0) * altura;
+        iluminar(self.roca.lerp(&self.nieve, factor), &Material::default(), fragment, uniforms)
+    }
+}
+
+/// Franjas de ruido que pasan del verde al morado y al amarillo.
+pub struct AuroraShader {
+    /// Color base donde el ruido es bajo.
+    pub verde: Color,
+    /// Color intermedio.
+    pub morado: Color,
+    /// Color donde el ruido es mas alto.
+    pub amarillo: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Valores altos dejan mas superficie en verde.
+    pub exponente: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+}
+
+impl Default for AuroraShader {
+    fn default() -> Self {
+        AuroraShader {
+            verde: Color::new(0, 255, 127),
+            morado: Color::new(75, 0, 130),
+            amarillo: Color::new(255, 255, 0),
+            escala: 5.0,
+            exponente: 2.0,
+            animacion: 0.0,
+        }
+    }
+}
+
+impl FragmentShader for AuroraShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = ((ruido + 1.0) / 2.0).powf(self.exponente);
+        iluminar(self.verde.lerp(&self.morado, factor).lerp(&self.amarillo, factor), &Material::default(), fragment, uniforms)
+    }
+}
+
+/// Superficie gris con bordes de crateres marcados por el ruido.
+pub struct CrateresShader {
+    /// Color del suelo.
+    pub gris_oscuro: Color,
+    /// Color de los bordes de los crateres.
+    pub gris_claro: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Valores altos dejan menos bordes claros.
+    pub exponente: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+}
+
+impl Default for CrateresShader {
+    fn default() -> Self {
+        CrateresShader {
+            gris_oscuro: Color::new(169, 169, 169),
+            gris_claro: Color::new(211, 211, 211),
+            escala: 15.0,
+            exponente: 1.5,
+            animacion: 0.0,
+        }
+    }
+}
+
+impl FragmentShader for CrateresShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let factor = (ruido * ruido).powf(self.exponente);
+        iluminar(self.gris_oscuro.lerp(&self.gris_claro, factor), &Material::default(), fragment, uniforms)
+    }
+}
+
+/// Una estrella: brilla con luz propia, asi que no usa `iluminar`. El ruido da
+/// la granulacion de la superficie y el borde se oscurece hacia `borde`, como
+/// se ve el sol por el espesor de su atmosfera.
+pub struct EstrellaShader {
+    /// Color de la superficie caliente.
+    pub nucleo: Color,
+    /// Color de las zonas mas frias de la granulacion.
+    pub mancha: Color,
+    /// Color hacia el que se oscurece el borde del disco.
+    pub borde: Color,
+    /// Frecuencia del ruido.
+    pub escala: f32,
+    /// Velocidad de la animacion.
+    pub animacion: f32,
+}
+
+impl Default for EstrellaShader {
+    fn default() -> Self {
+        EstrellaShader {
+            nucleo: Color::new(255, 244, 200),
+            mancha: Color::new(255, 170, 40),
+            borde: Color::new(220, 90, 10),
+            escala: 4.0,
+            animacion: 0.02,
+        }
+    }
+}
+
+impl FragmentShader for EstrellaShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let ruido = ruido_superficie(uniforms.noise, &fragment.vertex_position, self.escala, self.animacion, uniforms.time);
+        let superficie = self.nucleo.lerp(&self.mancha, ruido * 0.5 + 0.5);
+
+        let vista = (uniforms.camera_position - fragment.world_position).normalize();
+        let centro = fragment.normal.dot(&vista).clamp(0.0, 1.0);
+        superficie.lerp(&self.borde, (1.0 - centro).powf(2.0))
+    }
+}
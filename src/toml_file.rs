@@ -0,0 +1,95 @@
+//! Lectura de archivos TOML y validacion de sus campos, comun a los presets
+//! de ruido y a las escenas.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+
+/// Error al leer o validar un archivo TOML. `Campo` indica la ruta del campo
+/// culpable, como `fractal.octaves` u `objects[2].scale`; `Archivo` agrega el
+/// archivo donde estaba.
+#[derive(Debug)]
+pub enum TomlError {
+    /// No se pudo leer el archivo.
+    Io(PathBuf, io::Error),
+    /// El TOML no es valido o tiene campos desconocidos.
+    Sintaxis(String),
+    /// Un valor invalido.
+    Campo {
+        /// Ruta del campo, como `fractal.octaves`.
+        campo: String,
+        /// Que tiene de malo.
+        mensaje: String,
+    },
+    /// Un error dentro de un archivo.
+    Archivo {
+        /// El archivo.
+        path: PathBuf,
+        /// El error que tuvo.
+        error: Box<TomlError>,
+    },
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TomlError::Io(path, error) => write!(f, "no se pudo leer {}: {}", path.display(), error),
+            TomlError::Sintaxis(mensaje) => write!(f, "{}", mensaje.trim_end()),
+            TomlError::Campo { campo, mensaje } => write!(f, "campo `{}`: {}", campo, mensaje),
+            TomlError::Archivo { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+pub(crate) fn leer(path: &Path) -> Result<String, TomlError> {
+    fs::read_to_string(path).map_err(|error| TomlError::Io(path.to_path_buf(), error))
+}
+
+pub(crate) fn parse<T: DeserializeOwned>(texto: &str) -> Result<T, TomlError> {
+    toml::from_str(texto).map_err(|error| TomlError::Sintaxis(error.to_string()))
+}
+
+pub(crate) fn campo(campo: impl Into<String>, mensaje: impl Into<String>) -> TomlError {
+    TomlError::Campo { campo: campo.into(), mensaje: mensaje.into() }
+}
+
+/// Busca `valor` en una tabla de nombres; si no esta, el error lista las
+/// opciones validas.
+pub(crate) fn elegir<T: Copy>(nombre_campo: &str, valor: &str, opciones: &[(&str, T)]) -> Result<T, TomlError> {
+    opciones
+        .iter()
+        .find(|(nombre, _)| *nombre == valor)
+        .map(|(_, opcion)| *opcion)
+        .ok_or_else(|| {
+            let validas: Vec<&str> = opciones.iter().map(|(nombre, _)| *nombre).collect();
+            campo(nombre_campo, format!("valor desconocido \"{}\" (usar {})", valor, validas.join(", ")))
+        })
+}
+
+pub(crate) fn finito(nombre_campo: &str, valor: f32) -> Result<f32, TomlError> {
+    if valor.is_finite() {
+        Ok(valor)
+    } else {
+        Err(campo(nombre_campo, format!("debe ser un numero finito, no {}", valor)))
+    }
+}
+
+pub(crate) fn positivo(nombre_campo: &str, valor: f32) -> Result<f32, TomlError> {
+    if valor.is_finite() && valor > 0.0 {
+        Ok(valor)
+    } else {
+        Err(campo(nombre_campo, format!("debe ser mayor que 0, no {}", valor)))
+    }
+}
+
+pub(crate) fn no_negativo(nombre_campo: &str, valor: f32) -> Result<f32, TomlError> {
+    if valor.is_finite() && valor >= 0.0 {
+        Ok(valor)
+    } else {
+        Err(campo(nombre_campo, format!("no puede ser negativo, es {}", valor)))
+    }
+}
@@ -1,59 +1,251 @@
-use nalgebra_glm::{Vec3, dot};
+//! Rasterizacion de triangulos con prueba de profundidad, culling y mezcla.
+
+use nalgebra_glm::{Vec2, Vec3};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::color::Color;
+use crate::framebuffer::{BlendMode, RenderTarget};
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-  let mut fragments = Vec::new();
-  let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
+/// Que caras descartar antes de rasterizar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CullMode {
+    /// Dibuja todas las caras.
+    #[default]
+    None,
+    /// Descarta las caras traseras.
+    Back,
+    /// Descarta las caras frontales.
+    Front,
+}
 
-  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+impl CullMode {
+    /// El modo siguiente, en el orden `None`, `Back`, `Front`.
+    pub fn next(self) -> Self {
+        match self {
+            CullMode::None => CullMode::Back,
+            CullMode::Back => CullMode::Front,
+            CullMode::Front => CullMode::None,
+        }
+    }
+}
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+/// Orden de los vertices (visto desde la camara) que define una cara frontal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrontFace {
+    /// Antihorario.
+    #[default]
+    CounterClockwise,
+    /// Horario.
+    Clockwise,
+}
 
-  let triangle_area = edge_function(&a, &b, &c);
+impl FrontFace {
+    /// El orden contrario.
+    pub fn flip(self) -> Self {
+        match self {
+            FrontFace::CounterClockwise => FrontFace::Clockwise,
+            FrontFace::Clockwise => FrontFace::CounterClockwise,
+        }
+    }
+}
 
-  for y in min_y..=max_y {
-    for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+/// Como se rasterizan los triangulos de un dibujo.
+#[derive(Clone, Copy, Debug)]
+pub struct RasterState {
+    /// Que caras se descartan.
+    pub cull_mode: CullMode,
+    /// Que orden de vertices define la cara frontal.
+    pub front_face: FrontFace,
+    /// Rasterizar por tiles en varios hilos (ver `tiles::render_tiled`).
+    pub tiled: bool,
+    /// Como se combina cada fragmento con el pixel.
+    pub blend: BlendMode,
+    /// Si los fragmentos que pasan la prueba de profundidad actualizan el
+    /// z-buffer. Las capas transparentes o aditivas lo dejan en falso.
+    pub depth_write: bool,
+}
+
+impl Default for RasterState {
+    fn default() -> Self {
+        RasterState {
+            cull_mode: CullMode::default(),
+            front_face: FrontFace::default(),
+            tiled: false,
+            blend: BlendMode::Opaque,
+            depth_write: true,
+        }
+    }
+}
+
+impl RasterState {
+    /// Decide si un triangulo se dibuja a partir de su area con signo en
+    /// pantalla (`edge_function(a, b, c)`). Como el viewport invierte el eje y,
+    /// un triangulo antihorario en NDC tiene area positiva.
+    pub fn is_visible(&self, signed_area: f32) -> bool {
+        let frontal = match self.front_face {
+            FrontFace::CounterClockwise => signed_area > 0.0,
+            FrontFace::Clockwise => signed_area < 0.0,
+        };
+
+        match self.cull_mode {
+            CullMode::None => true,
+            CullMode::Back => frontal,
+            CullMode::Front => !frontal,
+        }
+    }
+}
+
+// Las posiciones en pantalla se redondean a 1/256 de pixel y las funciones
+// de arista se evaluan en enteros, asi que dos triangulos que comparten una
+// arista obtienen exactamente los mismos valores (con signo opuesto).
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL: i64 = 1 << SUBPIXEL_BITS;
+
+type FixedPoint = (i64, i64);
+
+/// Rasteriza un triangulo ya en pantalla directamente sobre el destino
+/// (framebuffer o tile), recortando la caja envolvente a sus limites.
+///
+/// Las funciones de arista se avanzan de forma incremental por filas y
+/// columnas, y los pixeles que caen justo sobre una arista se resuelven con la
+/// regla top-left, de modo que cada pixel de una malla cerrada se cubre una
+/// sola vez. Cada pixel pasa primero por el z-buffer y solo los fragmentos
+/// visibles llegan a `shade`, asi no se sombrean fragmentos ocultos.
+///
+/// `shade` devuelve el color y su opacidad, que se combinan con el destino
+/// segun `state.blend`.
+pub fn triangle<T, F>(v1: &Vertex, v2: &Vertex, v3: &Vertex, state: &RasterState, target: &mut T, mut shade: F)
+where
+  T: RenderTarget,
+  F: FnMut(&Fragment) -> (Color, f32),
+{
+  let (mut v2, mut v3) = (v2, v3);
+  let a = to_fixed(&v1.transformed_position);
+  let mut b = to_fixed(&v2.transformed_position);
+  let mut c = to_fixed(&v3.transformed_position);
+
+  let mut triangle_area = edge_function(&a, &b, &c);
+  if triangle_area == 0 || !state.is_visible(triangle_area as f32) {
+    return;
+  }
+
+  // Se normaliza a area positiva para que el interior siempre tenga
+  // funciones de arista >= 0.
+  if triangle_area < 0 {
+    std::mem::swap(&mut v2, &mut v3);
+    std::mem::swap(&mut b, &mut c);
+    triangle_area = -triangle_area;
+  }
 
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+  let half = SUBPIXEL / 2;
+  let (target_min_x, target_min_y, target_max_x, target_max_y) = target.bounds();
+  let min_x = (a.0.min(b.0).min(c.0) - half).div_euclid(SUBPIXEL).max(target_min_x as i64);
+  let min_y = (a.1.min(b.1).min(c.1) - half).div_euclid(SUBPIXEL).max(target_min_y as i64);
+  let max_x = (a.0.max(b.0).max(c.0) - half).div_euclid(SUBPIXEL).min(target_max_x as i64 - 1);
+  let max_y = (a.1.max(b.1).max(c.1) - half).div_euclid(SUBPIXEL).min(target_max_y as i64 - 1);
+  if min_x > max_x || min_y > max_y {
+    return;
+  }
 
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+  // Arista opuesta a cada vertice: e0 pesa a v1, e1 a v2 y e2 a v3.
+  let edges = [Edge::new(&b, &c), Edge::new(&c, &a), Edge::new(&a, &b)];
+  let origin = (min_x * SUBPIXEL + half, min_y * SUBPIXEL + half);
+  let mut row = [
+    edge_function(&b, &c, &origin),
+    edge_function(&c, &a, &origin),
+    edge_function(&a, &b, &origin),
+  ];
 
-        let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = normal.normalize();
+  let (za, zb, zc) = (v1.transformed_position.z, v2.transformed_position.z, v3.transformed_position.z);
+  let area = triangle_area as f32;
 
-        let intensity = dot(&normal, &light_dir).max(0.0);
+  for y in min_y..=max_y {
+    let mut e = row;
 
-        let base_color = Color::new(100, 100, 100);
-        let lit_color = base_color * intensity;
+    for x in min_x..=max_x {
+      if edges[0].covers(e[0]) && edges[1].covers(e[1]) && edges[2].covers(e[2]) {
+        let w1 = e[0] as f32 / area;
+        let w2 = e[1] as f32 / area;
+        let w3 = e[2] as f32 / area;
 
-        let depth = a.z * w1 + b.z * w2 + c.z * w3;
+        let depth = za * w1 + zb * w2 + zc * w3;
+        if target.depth_test(x as usize, y as usize, depth) {
+          // Los atributos se interpolan con correccion de perspectiva: se pesan
+          // con 1/w de cada vertice y se renormaliza. La profundidad ya esta
+          // dividida entre w, asi que esa si es lineal en pantalla.
+          let p1 = w1 * v1.inv_w;
+          let p2 = w2 * v2.inv_w;
+          let p3 = w3 * v3.inv_w;
+          let suma = p1 + p2 + p3;
+          let (p1, p2, p3) = (p1 / suma, p2 / suma, p3 / suma);
 
-        let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+          let normal = v1.transformed_normal * p1 + v2.transformed_normal * p2 + v3.transformed_normal * p3;
+          let normal = normal.normalize();
 
-        fragments.push(
-            Fragment::new(
-                x as f32,
-                y as f32,
-                lit_color,
-                depth,
-                normal,
-                intensity,
-                vertex_position,
-            )
-        );
+          let world_position = v1.world_position * p1 + v2.world_position * p2 + v3.world_position * p3;
+          let vertex_position = v1.position * p1 + v2.position * p2 + v3.position * p3;
+          let tex_coords = v1.tex_coords * p1 + v2.tex_coords * p2 + v3.tex_coords * p3;
+
+          let fragment = Fragment {
+            position: Vec2::new(x as f32, y as f32),
+            depth,
+            normal,
+            world_position,
+            vertex_position,
+            tex_coords,
+          };
+
+          let (color, alpha) = shade(&fragment);
+          let depth = state.depth_write.then_some(depth);
+          target.write(x as usize, y as usize, color.to_hex(), alpha, state.blend, depth);
+        }
+      }
+
+      for (value, edge) in e.iter_mut().zip(edges.iter()) {
+        *value += edge.step_x;
       }
     }
+
+    for (value, edge) in row.iter_mut().zip(edges.iter()) {
+      *value += edge.step_y;
+    }
   }
+}
+
+/// Incrementos de una funcion de arista al avanzar un pixel y regla top-left.
+struct Edge {
+    step_x: i64,
+    step_y: i64,
+    /// Valor minimo que cuenta como adentro: 0 en aristas superiores o
+    /// izquierdas, 1 en las demas para que un pixel exactamente sobre la
+    /// arista lo tome solo uno de los dos triangulos que la comparten.
+    min_value: i64,
+}
+
+impl Edge {
+    fn new(from: &FixedPoint, to: &FixedPoint) -> Self {
+        let dx = to.0 - from.0;
+        let dy = to.1 - from.1;
+        // Con area positiva el interior queda del lado (dy, -dx): la arista es
+        // izquierda si el interior esta hacia +x y superior si es horizontal
+        // con el interior hacia +y (abajo en pantalla).
+        let top_left = dy > 0 || (dy == 0 && dx < 0);
 
-  fragments
+        Edge {
+            step_x: dy * SUBPIXEL,
+            step_y: -dx * SUBPIXEL,
+            min_value: if top_left { 0 } else { 1 },
+        }
+    }
+
+    fn covers(&self, value: i64) -> bool {
+        value >= self.min_value
+    }
 }
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
+/// Caja envolvente de tres puntos en pantalla, redondeada hacia afuera a
+/// pixeles enteros, como (min_x, min_y, max_x, max_y).
+pub fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
     let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
@@ -62,14 +254,13 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
-
-    (w1, w2, w3)
+fn to_fixed(v: &Vec3) -> FixedPoint {
+    (
+        (v.x * SUBPIXEL as f32).round() as i64,
+        (v.y * SUBPIXEL as f32).round() as i64,
+    )
 }
 
-fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
-    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
-}
\ No newline at end of file
+fn edge_function(a: &FixedPoint, b: &FixedPoint, c: &FixedPoint) -> i64 {
+    (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
+}
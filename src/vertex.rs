@@ -1,31 +1,69 @@
-use nalgebra_glm::{Vec2, Vec3};
+//! Vertices de malla y los atributos que les agrega el pipeline.
+
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
 
+/// Un vertice de la malla junto con los resultados del vertex shader.
+///
+/// `position`, `normal` y `tex_coords` vienen de la malla (espacio del
+/// objeto). El vertex shader llena `clip_position`, `world_position` y
+/// `transformed_normal`; `transformed_position` e `inv_w` se calculan al pasar
+/// a pantalla, despues del recorte.
 #[derive(Clone, Debug)]
 pub struct Vertex {
+  /// Posicion en espacio del objeto.
   pub position: Vec3,
+  /// Normal en espacio del objeto.
   pub normal: Vec3,
+  /// Coordenadas de textura.
   pub tex_coords: Vec2,
+  /// Color del vertice.
   pub color: Color,
+  /// Posicion en clip space; sobre ella trabaja `clipping::clip_triangle`.
+  pub clip_position: Vec4,
+  /// Posicion en espacio de mundo.
+  pub world_position: Vec3,
+  /// Posicion en pantalla: x e y en pixeles y z la profundidad ya dividida
+  /// entre w. La escribe `clipping::to_screen`; el vertex shader la deja en
+  /// cero.
   pub transformed_position: Vec3,
+  /// Normal en espacio de mundo.
   pub transformed_normal: Vec3,
+  /// `1 / clip_position.w`, para interpolar con correccion de perspectiva.
+  pub inv_w: f32,
 }
 
 impl Vertex {
+  /// Vertice de malla; los campos que llena el pipeline empiezan con los
+  /// valores de la malla.
   pub fn new(position: Vec3, normal: Vec3, tex_coords: Vec2) -> Self {
     Vertex {
       position,
       normal,
       tex_coords,
       color: Color::black(),
+      clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
+      world_position: position,
       transformed_position: position,
       transformed_normal: normal,
+      inv_w: 1.0,
     }
   }
 
-
-
-
+  /// Interpola todos los atributos hacia `other`; lo usa el recorte.
+  pub fn lerp(&self, other: &Vertex, t: f32) -> Self {
+    Vertex {
+      position: self.position + (other.position - self.position) * t,
+      normal: self.normal + (other.normal - self.normal) * t,
+      tex_coords: self.tex_coords + (other.tex_coords - self.tex_coords) * t,
+      color: self.color.lerp(&other.color, t),
+      clip_position: self.clip_position + (other.clip_position - self.clip_position) * t,
+      world_position: self.world_position + (other.world_position - self.world_position) * t,
+      transformed_position: self.transformed_position + (other.transformed_position - self.transformed_position) * t,
+      transformed_normal: self.transformed_normal + (other.transformed_normal - self.transformed_normal) * t,
+      inv_w: self.inv_w + (other.inv_w - self.inv_w) * t,
+    }
+  }
 }
 
 impl Default for Vertex {
@@ -35,8 +73,11 @@ impl Default for Vertex {
       normal: Vec3::new(0.0, 1.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
       color: Color::black(),
+      clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      inv_w: 1.0,
     }
   }
-}
\ No newline at end of file
+}
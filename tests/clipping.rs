@@ -0,0 +1,117 @@
+//! Pruebas del recorte contra el frustum de `lab4::clipping`.
+
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+use lab4::clipping::{clip_triangle, inside_frustum, to_screen};
+use lab4::pipeline::create_viewport_matrix;
+use lab4::vertex::Vertex;
+
+const EPSILON: f32 = 1e-5;
+
+/// Los seis planos como coeficientes (x, y, z, w): adentro es >= 0.
+const PLANOS: [(&str, Vec4); 6] = [
+    ("near", Vec4::new(0.0, 0.0, 1.0, 1.0)),
+    ("far", Vec4::new(0.0, 0.0, -1.0, 1.0)),
+    ("left", Vec4::new(1.0, 0.0, 0.0, 1.0)),
+    ("right", Vec4::new(-1.0, 0.0, 0.0, 1.0)),
+    ("bottom", Vec4::new(0.0, 1.0, 0.0, 1.0)),
+    ("top", Vec4::new(0.0, -1.0, 0.0, 1.0)),
+];
+
+fn en_clip(clip: Vec4, u: f32) -> Vertex {
+    Vertex {
+        clip_position: clip,
+        tex_coords: Vec2::new(u, 0.0),
+        ..Vertex::default()
+    }
+}
+
+fn margenes(vertex: &Vertex) -> Vec4 {
+    let clip = vertex.clip_position;
+    Vec4::new(clip.w - clip.x.abs(), clip.w - clip.y.abs(), clip.w - clip.z.abs(), clip.w)
+}
+
+#[test]
+fn triangle_inside_is_untouched() {
+    let vertices = [
+        en_clip(Vec4::new(-0.5, -0.5, 0.0, 1.0), 0.0),
+        en_clip(Vec4::new(0.5, -0.5, 0.2, 1.0), 0.5),
+        en_clip(Vec4::new(0.0, 0.5, -0.2, 1.0), 1.0),
+    ];
+    assert!(vertices.iter().all(inside_frustum));
+    let polygon = clip_triangle(&vertices[0], &vertices[1], &vertices[2]);
+
+    assert_eq!(polygon.len(), 3);
+    for (original, recortado) in vertices.iter().zip(&polygon) {
+        assert_eq!(original.clip_position, recortado.clip_position);
+    }
+}
+
+#[test]
+fn triangle_is_clipped_against_each_plane() {
+    for (nombre, plano) in PLANOS {
+        // Un vertice queda afuera solo de este plano, a la misma distancia
+        // que el de adentro, asi el corte cae a la mitad de cada arista.
+        let normal = plano.xyz();
+        let afuera = en_clip(Vec4::new(-2.0 * normal.x, -2.0 * normal.y, -2.0 * normal.z, 1.0), 1.0);
+        let a = en_clip(Vec4::new(0.0, 0.0, 0.0, 1.0), 0.0);
+        let lado = Vec3::new(normal.y + normal.z, normal.x + normal.z, 0.0) * 0.5;
+        let b = en_clip(Vec4::new(lado.x, lado.y, lado.z, 1.0), 0.0);
+
+        assert!(!inside_frustum(&afuera) && inside_frustum(&a) && inside_frustum(&b), "{}", nombre);
+        let polygon = clip_triangle(&afuera, &a, &b);
+        assert_eq!(polygon.len(), 4, "{}: el triangulo recortado deberia ser un cuadrilatero", nombre);
+
+        let mut sobre_el_plano = 0;
+        for vertex in &polygon {
+            let distancia = plano.dot(&vertex.clip_position);
+            assert!(distancia >= -EPSILON, "{}: vertice afuera {:?}", nombre, vertex.clip_position);
+            assert!(margenes(vertex).iter().all(|&d| d >= -EPSILON), "{}: vertice fuera del frustum", nombre);
+            if distancia.abs() < EPSILON {
+                sobre_el_plano += 1;
+                // Los atributos se interpolan igual que la posicion.
+                assert!((vertex.tex_coords.x - 0.5).abs() < EPSILON, "{}: tex_coords {:?}", nombre, vertex.tex_coords);
+            }
+        }
+        assert_eq!(sobre_el_plano, 2, "{}", nombre);
+    }
+}
+
+#[test]
+fn triangle_outside_a_plane_is_dropped() {
+    for (nombre, plano) in PLANOS {
+        let normal = plano.xyz() * -2.0;
+        let vertices: Vec<Vertex> = [0.0, 0.3, -0.3]
+            .iter()
+            .map(|&corrimiento| {
+                let clip = Vec4::new(normal.x + corrimiento, normal.y + corrimiento, normal.z + corrimiento, 1.0);
+                en_clip(clip, 0.0)
+            })
+            .collect();
+        assert!(clip_triangle(&vertices[0], &vertices[1], &vertices[2]).is_empty(), "{}", nombre);
+    }
+}
+
+#[test]
+fn vertices_behind_the_camera_are_clipped_before_dividing() {
+    // Con w negativo la division perspectiva invertiria el vertice; el
+    // recorte contra near lo evita.
+    let frente = en_clip(Vec4::new(0.0, 0.0, 1.0, 2.0), 0.0);
+    let derecha = en_clip(Vec4::new(1.0, 0.0, 1.0, 2.0), 0.0);
+    let detras = en_clip(Vec4::new(0.0, 1.0, -3.0, -1.0), 1.0);
+
+    let polygon = clip_triangle(&frente, &derecha, &detras);
+    assert!(!polygon.is_empty());
+    assert!(polygon.iter().all(|vertex| vertex.clip_position.w > 0.0));
+}
+
+#[test]
+fn to_screen_divides_by_w() {
+    let vertex = en_clip(Vec4::new(1.0, -1.0, 0.5, 2.0), 0.0);
+    let screen = to_screen(&vertex, &Mat4::identity());
+    assert_eq!(screen.transformed_position, Vec3::new(0.5, -0.5, 0.25));
+    assert_eq!(screen.inv_w, 0.5);
+
+    let viewport = create_viewport_matrix(100.0, 50.0);
+    let centro = to_screen(&en_clip(Vec4::new(0.0, 0.0, 0.0, 3.0), 0.0), &viewport);
+    assert!((centro.transformed_position.xy() - Vec2::new(50.0, 25.0)).magnitude() < EPSILON);
+}
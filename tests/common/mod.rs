@@ -0,0 +1,20 @@
+//! Ayudas compartidas por las pruebas de los archivos TOML.
+
+use std::fmt::Debug;
+use lab4::toml_file::TomlError;
+
+/// El error de `resultado`; la prueba falla si `caso` se acepto.
+pub fn error_de<T, E>(resultado: Result<T, E>, caso: impl Debug) -> E {
+    match resultado {
+        Ok(_) => panic!("{:?} deberia ser invalido", caso),
+        Err(error) => error,
+    }
+}
+
+/// Revisa que `error` sea un error de campo en `esperado`.
+pub fn assert_campo(error: &TomlError, esperado: &str, caso: impl Debug) {
+    match error {
+        TomlError::Campo { campo, .. } => assert_eq!(campo, esperado, "{:?}", caso),
+        otro => panic!("se esperaba un error de campo para {:?}, no {}", caso, otro),
+    }
+}
@@ -0,0 +1,212 @@
+//! Pruebas de regresion visual: cada shader del registro de planetas se
+//! renderiza sobre `assets/sphere.obj` con camara y tiempo fijos y se compara
+//! contra una imagen de referencia en `tests/golden/`.
+//!
+//! Para regenerar las referencias despues de un cambio visual intencional:
+//! `UPDATE_GOLDEN=1 cargo test --test golden`.
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use nalgebra_glm::Vec3;
+use lab4::camera::Camera;
+use lab4::framebuffer::Framebuffer;
+use lab4::image;
+use lab4::lighting::Lighting;
+use lab4::obj::Obj;
+use lab4::pipeline::{render_frame, render_scene, FrameContext};
+use lab4::registry::{planetas, ShaderRegistry};
+use lab4::ruido::{presets, NoiseLibrary};
+use lab4::scene_file;
+use lab4::triangle::{CullMode, FrontFace, RasterState};
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 128;
+const TIME: u32 = 10;
+/// Diferencia maxima permitida por canal en cada pixel.
+const TOLERANCE: u8 = 2;
+/// Sol de costado para las pruebas del lado nocturno.
+const SOL_LATERAL: Vec3 = Vec3::new(-20.0, 5.0, 10.0);
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Renderiza un planeta del registro. Sin `sun` se ilumina de frente con
+/// `Lighting::default()`; con `sun` se ilumina desde esa posicion.
+fn render(registry: &ShaderRegistry, noise_library: &NoiseLibrary, name: &str, tiled: bool, sun: Option<Vec3>) -> Framebuffer {
+    let obj = Obj::load(manifest_dir().join("assets/sphere.obj").to_str().unwrap()).unwrap();
+    let vertex_array = obj.get_vertex_array();
+
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    framebuffer.set_background_color(0x009965);
+
+    let camera = Camera::new(
+        Vec3::new(0.0, 0.0, 2.5),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
+    );
+    let raster_state = RasterState {
+        cull_mode: CullMode::Back,
+        front_face: FrontFace::CounterClockwise,
+        tiled,
+        ..RasterState::default()
+    };
+
+    let entry = registry.get(name).unwrap();
+    let noise = noise_library.get(&entry.noise).unwrap();
+    let (lighting, sun_position) = match sun {
+        Some(position) => (Lighting::sun(position), position),
+        None => (Lighting::default(), Vec3::new(0.0, 0.0, 1000.0)),
+    };
+    let frame = FrameContext { camera: &camera, lighting: &lighting, sun_position, time: TIME };
+    render_frame(&mut framebuffer, &frame, &vertex_array, &raster_state, entry, noise);
+    framebuffer
+}
+
+/// Renderiza `assets/escenas/sistema_solar.toml` desde su camara inicial, al
+/// doble de tamano que los planetas sueltos para que se distingan los mas
+/// chicos.
+fn render_sistema_solar(tiled: bool) -> Framebuffer {
+    let scene = scene_file::load(&manifest_dir().join("assets/escenas/sistema_solar.toml")).unwrap();
+    let registry = planetas();
+    let noise_library = presets();
+    scene.validate(&registry, &noise_library).unwrap();
+
+    let mut framebuffer = Framebuffer::new(WIDTH * 2, HEIGHT * 2);
+    let raster_state = RasterState {
+        cull_mode: CullMode::Back,
+        front_face: FrontFace::CounterClockwise,
+        tiled,
+        ..RasterState::default()
+    };
+    let frame = FrameContext { camera: &scene.camera, lighting: &scene.lighting, sun_position: scene.sun_position, time: TIME };
+    render_scene(&mut framebuffer, &frame, &scene, &registry, &noise_library, &raster_state);
+    framebuffer
+}
+
+fn load_png(path: &Path) -> io::Result<(usize, usize, Vec<u32>)> {
+    let decoder = png::Decoder::new(File::open(path)?);
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).map_err(io::Error::other)?;
+
+    if info.color_type != png::ColorType::Rgb || info.bit_depth != png::BitDepth::Eight {
+        return Err(io::Error::other("se esperaba un PNG RGB de 8 bits"));
+    }
+
+    let pixels = data[..info.buffer_size()]
+        .chunks(3)
+        .map(|p| ((p[0] as u32) << 16) | ((p[1] as u32) << 8) | p[2] as u32)
+        .collect();
+    Ok((info.width as usize, info.height as usize, pixels))
+}
+
+fn channel_diff(a: u32, b: u32) -> u8 {
+    (0..3)
+        .map(|i| {
+            let shift = i * 8;
+            ((a >> shift) as u8).abs_diff((b >> shift) as u8)
+        })
+        .max()
+        .unwrap()
+}
+
+/// Compara contra la referencia y devuelve un mensaje si no coincide. En ese
+/// caso deja la imagen obtenida y un mapa de diferencias en
+/// `target/golden-diff/`.
+fn check(name: &str, framebuffer: &Framebuffer) -> Option<String> {
+    let reference = manifest_dir().join("tests/golden").join(format!("{}.png", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(reference.parent().unwrap()).unwrap();
+        framebuffer.save(&reference).unwrap();
+        return None;
+    }
+
+    let (width, height, expected) = match load_png(&reference) {
+        Ok(image) => image,
+        Err(error) => return Some(format!("{}: no se pudo leer {}: {}", name, reference.display(), error)),
+    };
+    if (width, height) != (framebuffer.width, framebuffer.height) {
+        return Some(format!("{}: la referencia mide {}x{}", name, width, height));
+    }
+
+    let diffs: Vec<u8> = expected
+        .iter()
+        .zip(&framebuffer.buffer)
+        .map(|(&a, &b)| channel_diff(a, b))
+        .collect();
+    let malos = diffs.iter().filter(|&&d| d > TOLERANCE).count();
+    if malos == 0 {
+        return None;
+    }
+
+    let out_dir = manifest_dir().join("target/golden-diff");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let diff_image: Vec<u32> = diffs
+        .iter()
+        .map(|&d| if d > TOLERANCE { 0xFF0000 | ((d as u32) << 8) } else { 0x000000 })
+        .collect();
+    framebuffer.save(&out_dir.join(format!("{}.png", name))).unwrap();
+    image::save(&out_dir.join(format!("{}_diff.png", name)), width, height, &diff_image).unwrap();
+
+    Some(format!(
+        "{}: {} pixeles difieren en mas de {} (ver {})",
+        name,
+        malos,
+        TOLERANCE,
+        out_dir.display()
+    ))
+}
+
+#[test]
+fn golden_planet_shaders() {
+    let registry = planetas();
+    let noise_library = presets();
+    let fallos: Vec<String> = registry
+        .names()
+        .filter_map(|name| check(name, &render(&registry, &noise_library, name, false, None)))
+        .collect();
+
+    assert!(fallos.is_empty(), "imagenes distintas a la referencia:\n{}", fallos.join("\n"));
+}
+
+#[test]
+fn golden_tiled_matches_single_threaded() {
+    let registry = planetas();
+    let noise_library = presets();
+    for name in registry.names() {
+        let single = render(&registry, &noise_library, name, false, None);
+        let tiled = render(&registry, &noise_library, name, true, None);
+        assert!(single.buffer == tiled.buffer, "{}: el render por tiles no coincide", name);
+        assert!(single.zbuffer == tiled.zbuffer, "{}: el z-buffer por tiles no coincide", name);
+    }
+}
+
+#[test]
+fn golden_night_side_city_lights() {
+    let registry = planetas();
+    let noise_library = presets();
+    let fallos: Vec<String> = ["jungla", "desierto"]
+        .iter()
+        .filter_map(|name| {
+            let framebuffer = render(&registry, &noise_library, name, false, Some(SOL_LATERAL));
+            check(&format!("{}_noche", name), &framebuffer)
+        })
+        .collect();
+
+    assert!(fallos.is_empty(), "imagenes distintas a la referencia:\n{}", fallos.join("\n"));
+}
+
+#[test]
+fn golden_solar_system_scene() {
+    let single = render_sistema_solar(false);
+    let tiled = render_sistema_solar(true);
+    assert!(single.buffer == tiled.buffer, "sistema_solar: el render por tiles no coincide");
+    assert!(single.zbuffer == tiled.zbuffer, "sistema_solar: el z-buffer por tiles no coincide");
+
+    if let Some(fallo) = check("sistema_solar", &single) {
+        panic!("imagen distinta a la referencia:\n{}", fallo);
+    }
+}
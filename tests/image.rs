@@ -0,0 +1,128 @@
+//! Pruebas de los codificadores de `lab4::image` y de `Framebuffer::save`.
+
+use std::path::PathBuf;
+use lab4::framebuffer::Framebuffer;
+use lab4::image::{self, ImageFormat};
+
+/// Pixeles distintos entre si para notar filas o canales cambiados.
+fn pixeles(width: usize, height: usize) -> Vec<u32> {
+    (0..width * height)
+        .map(|i| {
+            let i = i as u32;
+            ((i * 37) & 0xff) << 16 | ((i * 91 + 7) & 0xff) << 8 | ((i * 13 + 200) & 0xff)
+        })
+        .collect()
+}
+
+fn u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Lee un PPM P6 de 8 bits como el que escribe `encode_ppm`.
+fn decode_ppm(data: &[u8]) -> (usize, usize, Vec<u32>) {
+    let mut campos = Vec::new();
+    let mut inicio = 0;
+    for (i, &byte) in data.iter().enumerate() {
+        if byte.is_ascii_whitespace() {
+            campos.push(std::str::from_utf8(&data[inicio..i]).unwrap());
+            inicio = i + 1;
+            if campos.len() == 4 {
+                break;
+            }
+        }
+    }
+    assert_eq!(campos[0], "P6");
+    assert_eq!(campos[3], "255");
+    let (width, height) = (campos[1].parse().unwrap(), campos[2].parse().unwrap());
+    let pixels = data[inicio..]
+        .chunks(3)
+        .map(|c| (c[0] as u32) << 16 | (c[1] as u32) << 8 | c[2] as u32)
+        .collect();
+    (width, height, pixels)
+}
+
+/// Lee un BMP de 24 bits como el que escribe `encode_bmp`, respetando el
+/// relleno de cada fila.
+fn decode_bmp(data: &[u8]) -> (usize, usize, Vec<u32>) {
+    assert_eq!(&data[0..2], b"BM");
+    let offset = u32_le(data, 10) as usize;
+    let width = u32_le(data, 18) as usize;
+    let height = u32_le(data, 22) as usize;
+    let row_size = (width * 3).div_ceil(4) * 4;
+
+    let mut pixels = vec![0; width * height];
+    for (fila, row) in data[offset..].chunks(row_size).enumerate() {
+        let y = height - 1 - fila;
+        for x in 0..width {
+            let [b, g, r] = [row[x * 3], row[x * 3 + 1], row[x * 3 + 2]];
+            pixels[y * width + x] = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+        }
+    }
+    (width, height, pixels)
+}
+
+#[test]
+fn ppm_header_and_round_trip() {
+    let pixels = pixeles(5, 3);
+    let data = image::encode_ppm(5, 3, &pixels);
+
+    assert!(data.starts_with(b"P6\n5 3\n255\n"));
+    assert_eq!(data.len(), "P6\n5 3\n255\n".len() + 5 * 3 * 3);
+    assert_eq!(decode_ppm(&data), (5, 3, pixels));
+}
+
+#[test]
+fn bmp_header_and_row_padding() {
+    // Con ancho impar cada fila de 15 bytes se rellena a 16.
+    let (width, height) = (5, 3);
+    let pixels = pixeles(width, height);
+    let data = image::encode_bmp(width, height, &pixels);
+
+    let row_size = 16;
+    assert_eq!(data.len(), 14 + 40 + row_size * height);
+    assert_eq!(u32_le(&data, 2) as usize, data.len());
+    assert_eq!(u32_le(&data, 10), 54);
+    assert_eq!(u32_le(&data, 14), 40);
+    assert_eq!(u32_le(&data, 18) as usize, width);
+    assert_eq!(u32_le(&data, 22) as usize, height);
+    assert_eq!(u16::from_le_bytes([data[28], data[29]]), 24);
+    assert_eq!(u32_le(&data, 34) as usize, row_size * height);
+
+    // La primera fila guardada es la de abajo, en BGR, y el relleno es cero.
+    let [b, g, r] = [data[54], data[55], data[56]];
+    assert_eq!((r as u32) << 16 | (g as u32) << 8 | b as u32, pixels[(height - 1) * width]);
+    for fila in 0..height {
+        assert_eq!(data[54 + fila * row_size + 15], 0, "relleno de la fila {}", fila);
+    }
+
+    assert_eq!(decode_bmp(&data), (width, height, pixels));
+}
+
+#[test]
+fn bmp_round_trip_for_every_padding() {
+    for width in 1..=4 {
+        let pixels = pixeles(width, 2);
+        assert_eq!(decode_bmp(&image::encode_bmp(width, 2, &pixels)), (width, 2, pixels), "ancho {}", width);
+    }
+}
+
+#[test]
+fn framebuffer_save_picks_the_format_from_the_extension() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let mut framebuffer = Framebuffer::new(3, 2);
+    framebuffer.buffer = pixeles(3, 2);
+
+    for format in [ImageFormat::Ppm, ImageFormat::Bmp, ImageFormat::Png] {
+        let path = dir.join(format!("framebuffer_save.{}", format.extension()));
+        framebuffer.save(&path).unwrap();
+        let esperado = match format {
+            ImageFormat::Ppm => framebuffer.to_ppm(),
+            ImageFormat::Bmp => framebuffer.to_bmp(),
+            ImageFormat::Png => framebuffer.to_png().unwrap(),
+        };
+        assert_eq!(std::fs::read(&path).unwrap(), esperado, "{:?}", format);
+    }
+
+    let error = framebuffer.save(&dir.join("framebuffer_save.gif")).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,83 @@
+//! Pruebas de `lab4::lighting`: lado oscuro, atenuacion de las luces puntuales
+//! y la forma del brillo especular.
+
+use lab4::color::Color;
+use lab4::lighting::{Light, Lighting, Material, SpecularModel};
+use nalgebra_glm::Vec3;
+
+const BLANCO: Color = Color::new(255, 255, 255);
+
+fn luces(lights: Vec<Light>) -> Lighting {
+    Lighting { lights, ambient: Color::new(100, 120, 140), model: SpecularModel::BlinnPhong }
+}
+
+/// Color de un punto en el origen con normal +z visto desde +z.
+fn iluminar(lighting: &Lighting, material: &Material) -> u32 {
+    let eye = Vec3::new(0.0, 0.0, 5.0);
+    lighting.shade(BLANCO, material, &Vec3::zeros(), &Vec3::z(), &eye).to_hex()
+}
+
+#[test]
+fn back_facing_normal_gets_only_ambient() {
+    let material = Material { ambient: 0.5, diffuse: 1.0, specular: 1.0, shininess: 8.0 };
+    let lighting = luces(vec![
+        Light::directional(Vec3::new(0.0, 0.0, 1.0), BLANCO, 1.0),
+        Light::point(Vec3::new(0.0, 0.0, -3.0), 10.0, BLANCO, 1.0),
+    ]);
+    let solo_ambiente = luces(Vec::new());
+
+    let eye = Vec3::new(0.0, 0.0, -5.0);
+    let trasera = lighting.shade(BLANCO, &material, &Vec3::zeros(), &Vec3::z(), &eye);
+    let esperado = solo_ambiente.shade(BLANCO, &material, &Vec3::zeros(), &Vec3::z(), &eye);
+    assert_eq!(trasera.to_hex(), esperado.to_hex());
+    assert_eq!(trasera.to_hex(), 0x323c46);
+}
+
+#[test]
+fn point_light_is_halved_at_range() {
+    let material = Material::default();
+    let puntual = luces(vec![Light::point(Vec3::new(0.0, 0.0, 10.0), 10.0, BLANCO, 1.0)]);
+    let mitad = luces(vec![Light::directional(Vec3::new(0.0, 0.0, -1.0), BLANCO, 0.5)]);
+
+    assert_eq!(iluminar(&puntual, &material), iluminar(&mitad, &material));
+    assert_eq!(iluminar(&puntual, &material), 0x7f7f7f);
+}
+
+#[test]
+fn infinite_range_has_no_falloff() {
+    let material = Material::default();
+    let directional = luces(vec![Light::directional(Vec3::new(0.0, 0.0, -1.0), BLANCO, 1.0)]);
+
+    for distancia in [1.0, 10.0, 1000.0] {
+        let puntual = luces(vec![Light::point(Vec3::new(0.0, 0.0, distancia), f32::INFINITY, BLANCO, 1.0)]);
+        assert_eq!(iluminar(&puntual, &material), iluminar(&directional, &material), "a distancia {}", distancia);
+    }
+}
+
+#[test]
+fn specular_peaks_at_mirror_and_blinn_is_wider() {
+    // Solo brillo especular, con la luz a 45 grados sobre +x.
+    let material = Material { ambient: 0.0, diffuse: 0.0, specular: 1.0, shininess: 16.0 };
+    let hacia_luz = Vec3::new(1.0, 0.0, 1.0).normalize();
+    let brillo = |model: SpecularModel, desvio: f32| {
+        let lighting = Lighting { model, ..luces(vec![Light::directional(-hacia_luz, BLANCO, 1.0)]) };
+        // La vista espejo esta a -45 grados; `desvio` la gira en el plano xz.
+        let angulo = (-45.0 + desvio).to_radians();
+        let eye = Vec3::new(angulo.sin(), 0.0, angulo.cos()) * 5.0;
+        lighting.shade(Color::black(), &material, &Vec3::zeros(), &Vec3::z(), &eye).to_vec3().x
+    };
+
+    for model in [SpecularModel::Phong, SpecularModel::BlinnPhong] {
+        assert_eq!(brillo(model, 0.0), 1.0, "{:?}", model);
+        for desvio in [-30.0, -10.0, -3.0, 3.0, 10.0, 30.0] {
+            assert!(brillo(model, desvio) < 1.0, "{:?} con desvio {}", model, desvio);
+        }
+    }
+    for desvio in [-20.0, -10.0, 10.0, 20.0] {
+        assert!(
+            brillo(SpecularModel::BlinnPhong, desvio) > brillo(SpecularModel::Phong, desvio),
+            "desvio {}",
+            desvio
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! Pruebas de las mallas de `lab4::mesh`.
+
+use lab4::mesh::anillo;
+use nalgebra_glm::Vec3;
+
+const EPSILON: f32 = 1e-5;
+
+#[test]
+fn ring_spans_inner_to_outer_radius() {
+    let segmentos = 32;
+    let vertices = anillo(0.7, 1.2, segmentos);
+    assert_eq!(vertices.len(), segmentos as usize * 6);
+
+    for vertex in &vertices {
+        let radio = vertex.position.magnitude();
+        let u = vertex.tex_coords.x;
+        assert!(u == 0.0 || u == 1.0, "u = {}", u);
+        // u = 0 es el borde interior y u = 1 el exterior.
+        let esperado = if u == 0.0 { 0.7 } else { 1.2 };
+        assert!((radio - esperado).abs() < EPSILON, "radio {} con u = {}", radio, u);
+        assert_eq!(vertex.position.y, 0.0);
+        assert_eq!(vertex.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!((0.0..=1.0).contains(&vertex.tex_coords.y));
+    }
+
+    // Cada triangulo es antihorario visto desde +y y toca los dos bordes.
+    for tri in vertices.chunks_exact(3) {
+        let normal = (tri[1].position - tri[0].position).cross(&(tri[2].position - tri[0].position));
+        assert!(normal.y > 0.0);
+        assert!(tri.iter().any(|v| v.tex_coords.x == 0.0) && tri.iter().any(|v| v.tex_coords.x == 1.0));
+    }
+}
+
+#[test]
+fn ring_has_at_least_three_segments() {
+    assert_eq!(anillo(1.0, 2.0, 0).len(), 18);
+    assert_eq!(anillo(1.0, 2.0, 3).len(), 18);
+}
@@ -0,0 +1,82 @@
+//! Pruebas de los combinadores de `lab4::noise_source`.
+
+use lab4::noise_source::{DomainWarp, Fbm, NoiseRs, NoiseSource, Ridged};
+use noise::{OpenSimplex, Perlin, Simplex};
+
+/// Puntos de una grilla de 9x9x9 entre -4 y 4 en cada eje.
+fn grilla() -> impl Iterator<Item = (f32, f32, f32)> {
+    // Fuera de los enteros, donde el ruido de gradiente vale 0.
+    let paso = |i: i32| -3.9 + 0.97 * i as f32;
+    (0..9).flat_map(move |i| (0..9).flat_map(move |j| (0..9).map(move |k| (paso(i), paso(j), paso(k)))))
+}
+
+fn fuentes(seed: u32) -> Vec<(&'static str, Box<dyn NoiseSource>)> {
+    vec![
+        ("noise_rs", Box::new(NoiseRs::new(Simplex::new(seed), 0.5))),
+        ("fbm", Box::new(Fbm::new(NoiseRs::new(Perlin::new(seed), 0.4), 5, 2.0, 0.5))),
+        ("ridged", Box::new(Ridged::new(NoiseRs::new(OpenSimplex::new(seed), 0.3), 6, 2.0, 0.5))),
+        (
+            "domain_warp",
+            Box::new(DomainWarp::new(
+                Fbm::new(NoiseRs::new(Simplex::new(seed), 0.3), 4, 2.0, 0.5),
+                NoiseRs::new(Perlin::new(seed + 4), 0.2),
+                2.0,
+            )),
+        ),
+    ]
+}
+
+#[test]
+fn output_stays_in_range() {
+    for (name, source) in fuentes(7) {
+        for (x, y, z) in grilla() {
+            let valor_2d = source.sample_2d(x, y);
+            let valor_3d = source.sample_3d(x, y, z);
+            assert!((-1.0..=1.0).contains(&valor_2d), "{} en ({}, {}): {}", name, x, y, valor_2d);
+            assert!((-1.0..=1.0).contains(&valor_3d), "{} en ({}, {}, {}): {}", name, x, y, z, valor_3d);
+        }
+    }
+}
+
+#[test]
+fn zero_octaves_return_zero() {
+    let fbm = Fbm::new(NoiseRs::new(Perlin::new(1), 0.4), 0, 2.0, 0.5);
+    let ridged = Ridged::new(NoiseRs::new(OpenSimplex::new(1), 0.3), 0, 2.0, 0.5);
+
+    for (x, y, z) in grilla() {
+        assert_eq!(fbm.sample_2d(x, y), 0.0);
+        assert_eq!(fbm.sample_3d(x, y, z), 0.0);
+        assert_eq!(ridged.sample_2d(x, y), 0.0);
+        assert_eq!(ridged.sample_3d(x, y, z), 0.0);
+    }
+}
+
+#[test]
+fn fixed_seed_is_deterministic() {
+    for ((name, a), (_, b)) in fuentes(42).into_iter().zip(fuentes(42)) {
+        for (x, y, z) in grilla() {
+            assert_eq!(a.sample_2d(x, y), b.sample_2d(x, y), "{}", name);
+            assert_eq!(a.sample_3d(x, y, z), b.sample_3d(x, y, z), "{}", name);
+        }
+    }
+
+    // Otra semilla da otro ruido.
+    let a = NoiseRs::new(Simplex::new(42), 0.5);
+    let b = NoiseRs::new(Simplex::new(43), 0.5);
+    assert!(grilla().any(|(x, y, z)| a.sample_3d(x, y, z) != b.sample_3d(x, y, z)));
+}
+
+#[test]
+fn domain_warp_without_amplitude_is_the_source() {
+    let source = Fbm::new(NoiseRs::new(Simplex::new(3), 0.3), 4, 2.0, 0.5);
+    let warp = DomainWarp::new(
+        Fbm::new(NoiseRs::new(Simplex::new(3), 0.3), 4, 2.0, 0.5),
+        NoiseRs::new(Perlin::new(7), 0.2),
+        0.0,
+    );
+
+    for (x, y, z) in grilla() {
+        assert_eq!(warp.sample_2d(x, y), source.sample_2d(x, y));
+        assert_eq!(warp.sample_3d(x, y, z), source.sample_3d(x, y, z));
+    }
+}
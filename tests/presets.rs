@@ -0,0 +1,48 @@
+mod common;
+
+use std::path::PathBuf;
+use lab4::preset::NoisePreset;
+use lab4::ruido::NoiseLibrary;
+use lab4::toml_file::TomlError;
+
+fn error_de(texto: &str) -> TomlError {
+    common::error_de(NoisePreset::parse(texto).and_then(|preset| preset.build()), texto)
+}
+
+#[test]
+fn shipped_presets_load() {
+    let mut library = NoiseLibrary::new();
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/ruido");
+    let cargados = library.load_dir(&dir).unwrap();
+
+    assert_eq!(cargados, 7);
+    for name in ["perlin", "cellular_bacteria", "cellular_agujero_negro", "camo", "variado", "grupos", "cellular_puntas"] {
+        assert!(library.contains(name), "falta el preset {}", name);
+    }
+}
+
+#[test]
+fn validation_errors_name_the_field() {
+    let casos = [
+        ("type = \"ruidoso\"", "type"),
+        ("frequency = -1.0", "frequency"),
+        ("[fractal]\ntype = \"fbm\"\noctaves = 0", "fractal.octaves"),
+        ("[fractal]\noctaves = 3", "fractal.type"),
+        ("[fractal]\ntype = \"fbm\"\nping_pong_strength = 2.0", "fractal.ping_pong_strength"),
+        ("type = \"perlin\"\n[cellular]\njitter = 1.0", "cellular"),
+        ("type = \"cellular\"\n[cellular]\nreturn_type = \"lejos\"", "cellular.return_type"),
+    ];
+
+    for (texto, campo_esperado) in casos {
+        common::assert_campo(&error_de(texto), campo_esperado, texto);
+    }
+}
+
+#[test]
+fn unknown_and_mistyped_fields_are_reported() {
+    let error = error_de("seed = 1\nfrecuencia = 0.1").to_string();
+    assert!(error.contains("frecuencia"), "{}", error);
+
+    let error = error_de("[fractal]\ntype = \"fbm\"\noctaves = \"muchas\"").to_string();
+    assert!(error.contains("octaves"), "{}", error);
+}
@@ -0,0 +1,165 @@
+//! Pruebas del rasterizador de `lab4::triangle`.
+
+use std::f32::consts::PI;
+use nalgebra_glm::{Vec2, Vec3};
+use lab4::color::Color;
+use lab4::framebuffer::{BlendMode, Framebuffer};
+use lab4::triangle::{triangle, CullMode, FrontFace, RasterState};
+use lab4::vertex::Vertex;
+
+const SIZE: usize = 64;
+
+fn en_pantalla(x: f32, y: f32) -> Vertex {
+    a_profundidad(x, y, 0.5)
+}
+
+fn a_profundidad(x: f32, y: f32, depth: f32) -> Vertex {
+    let mut vertex = Vertex::new(Vec3::new(x, y, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros());
+    vertex.transformed_position = Vec3::new(x, y, depth);
+    vertex
+}
+
+/// Dibuja el abanico `centro`-`borde[i]`-`borde[i + 1]` sumando 1 a cada
+/// canal en cada pixel cubierto, y revisa que todo pixel con el centro dentro
+/// del poligono quede en 1 y que ninguno se cubra dos veces.
+fn revisar_abanico(centro: (f32, f32), borde: &[(f32, f32)]) {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    let state = RasterState {
+        cull_mode: CullMode::None,
+        blend: BlendMode::Additive,
+        depth_write: false,
+        ..RasterState::default()
+    };
+
+    let centro_vertex = en_pantalla(centro.0, centro.1);
+    for i in 0..borde.len() {
+        let (a, b) = (borde[i], borde[(i + 1) % borde.len()]);
+        triangle(&centro_vertex, &en_pantalla(a.0, a.1), &en_pantalla(b.0, b.1), &state, &mut framebuffer, |_| {
+            (Color::new(1, 1, 1), 1.0)
+        });
+    }
+
+    // El borde es convexo; se acepta cualquier orden de vueltas.
+    let adentro = |px: f32, py: f32| {
+        let lados: Vec<f32> = (0..borde.len())
+            .map(|i| {
+                let (a, b) = (borde[i], borde[(i + 1) % borde.len()]);
+                (b.0 - a.0) * (py - a.1) - (b.1 - a.1) * (px - a.0)
+            })
+            .collect();
+        lados.iter().all(|&lado| lado > 1e-3) || lados.iter().all(|&lado| lado < -1e-3)
+    };
+
+    let mut interiores = 0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let pixel = framebuffer.buffer[y * SIZE + x];
+            assert!(pixel == 0 || pixel == 0x010101, "({}, {}) cubierto mas de una vez: {:06x}", x, y, pixel);
+            if adentro(x as f32 + 0.5, y as f32 + 0.5) {
+                assert_eq!(pixel, 0x010101, "hueco en ({}, {})", x, y);
+                interiores += 1;
+            }
+        }
+    }
+    assert!(interiores > 100);
+}
+
+#[test]
+fn fan_covers_each_interior_pixel_once() {
+    // Vertices fuera de la grilla de pixeles.
+    let borde: Vec<(f32, f32)> = (0..17)
+        .map(|i| {
+            let angulo = 0.3 + i as f32 * 2.0 * PI / 17.0;
+            (32.3 + 25.0 * angulo.cos(), 31.7 + 25.0 * angulo.sin())
+        })
+        .collect();
+    revisar_abanico((32.3, 31.7), &borde);
+
+    // El mismo abanico en el otro sentido.
+    let invertido: Vec<_> = borde.iter().rev().copied().collect();
+    revisar_abanico((32.3, 31.7), &invertido);
+}
+
+#[test]
+fn fan_with_edges_through_pixel_centers() {
+    // Los rayos horizontales, verticales y diagonales pasan justo por centros
+    // de pixel, donde decide la regla top-left.
+    let (cx, cy) = (32.5, 32.5);
+    let borde = [
+        (cx + 24.0, cy),
+        (cx + 24.0, cy + 24.0),
+        (cx, cy + 24.0),
+        (cx - 24.0, cy + 24.0),
+        (cx - 24.0, cy),
+        (cx - 24.0, cy - 24.0),
+        (cx, cy - 24.0),
+        (cx + 24.0, cy - 24.0),
+    ];
+    revisar_abanico((cx, cy), &borde);
+}
+
+#[test]
+fn occluded_fragments_are_not_shaded() {
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    let state = RasterState { cull_mode: CullMode::None, ..RasterState::default() };
+
+    // Primero un triangulo cercano que tapa la mitad izquierda.
+    let cercano = [a_profundidad(0.0, 0.0, 0.2), a_profundidad(40.0, 0.0, 0.2), a_profundidad(0.0, 64.0, 0.2)];
+    triangle(&cercano[0], &cercano[1], &cercano[2], &state, &mut framebuffer, |_| (Color::new(255, 0, 0), 1.0));
+    let tapados = framebuffer.buffer.clone();
+
+    // Despues uno lejano que cubre toda la pantalla: solo se sombrean los
+    // pixeles que el cercano no cubrio.
+    let lejano = [a_profundidad(0.0, 0.0, 0.8), a_profundidad(128.0, 0.0, 0.8), a_profundidad(0.0, 128.0, 0.8)];
+    let mut sombreados = Vec::new();
+    triangle(&lejano[0], &lejano[1], &lejano[2], &state, &mut framebuffer, |fragment| {
+        sombreados.push((fragment.position.x as usize, fragment.position.y as usize));
+        (Color::new(0, 0, 255), 1.0)
+    });
+
+    let cubiertos = tapados.iter().filter(|&&pixel| pixel != 0).count();
+    assert!(cubiertos > 500);
+    assert_eq!(sombreados.len(), SIZE * SIZE - cubiertos);
+    for (x, y) in sombreados {
+        assert_eq!(tapados[y * SIZE + x], 0, "se sombreo ({}, {}), tapado por el triangulo cercano", x, y);
+    }
+}
+
+#[test]
+fn culling_follows_mode_and_winding() {
+    // (cull, orden, visible con area positiva, visible con area negativa)
+    let casos = [
+        (CullMode::None, FrontFace::CounterClockwise, true, true),
+        (CullMode::None, FrontFace::Clockwise, true, true),
+        (CullMode::Back, FrontFace::CounterClockwise, true, false),
+        (CullMode::Back, FrontFace::Clockwise, false, true),
+        (CullMode::Front, FrontFace::CounterClockwise, false, true),
+        (CullMode::Front, FrontFace::Clockwise, true, false),
+    ];
+
+    for (cull_mode, front_face, positiva, negativa) in casos {
+        let state = RasterState { cull_mode, front_face, ..RasterState::default() };
+        assert_eq!(state.is_visible(12.5), positiva, "{:?} {:?} con area positiva", cull_mode, front_face);
+        assert_eq!(state.is_visible(-12.5), negativa, "{:?} {:?} con area negativa", cull_mode, front_face);
+    }
+}
+
+#[test]
+fn blend_modes_combine_with_the_pixel() {
+    let dst = 0x204080;
+    let src = 0xc06020;
+
+    // Opaco ignora la opacidad.
+    assert_eq!(BlendMode::Opaque.apply(dst, src, 0.25), src);
+
+    assert_eq!(BlendMode::Alpha.apply(dst, src, 1.0), src);
+    assert_eq!(BlendMode::Alpha.apply(dst, src, 0.0), dst);
+    assert_eq!(BlendMode::Alpha.apply(dst, src, 0.5), 0x705050);
+
+    assert_eq!(BlendMode::Additive.apply(dst, src, 0.0), dst);
+    assert_eq!(BlendMode::Additive.apply(dst, src, 0.5), 0x807090);
+    // Cada canal satura en 255 por separado.
+    assert_eq!(BlendMode::Additive.apply(dst, src, 1.0), 0xe0a0a0);
+    assert_eq!(BlendMode::Additive.apply(0xf0f010, 0x4020ff, 1.0), 0xffffff);
+    assert_eq!(BlendMode::Additive.apply(0xf00010, 0x400020, 1.0), 0xff0030);
+}
@@ -0,0 +1,55 @@
+//! Pruebas de `lab4::registry`: busqueda por nombre y el recorrido con N y B
+//! del visor.
+
+use lab4::registry::{planetas, ShaderDefaults, ShaderRegistry};
+use lab4::shaders::{AguaShader, HieloShader};
+
+#[test]
+fn next_and_previous_wrap_around() {
+    let registry = planetas();
+    let ultimo = registry.len() - 1;
+
+    assert_eq!(registry.next(0), 1);
+    assert_eq!(registry.next(ultimo), 0);
+    assert_eq!(registry.previous(0), ultimo);
+    assert_eq!(registry.previous(1), 0);
+
+    // Una vuelta completa en cada sentido pasa por todos los shaders.
+    let mut index = 0;
+    let mut vistos = Vec::new();
+    for _ in 0..registry.len() {
+        vistos.push(registry.entry(index).unwrap().name.clone());
+        index = registry.next(index);
+    }
+    assert_eq!(index, 0);
+    assert_eq!(vistos, registry.names().collect::<Vec<_>>());
+    for _ in 0..registry.len() {
+        index = registry.previous(index);
+    }
+    assert_eq!(index, 0);
+
+    assert!(registry.entry(registry.len()).is_none());
+    assert_eq!(ShaderRegistry::new().next(3), 0);
+    assert_eq!(ShaderRegistry::new().previous(3), 0);
+}
+
+#[test]
+fn register_replaces_in_place() {
+    let mut registry = ShaderRegistry::new();
+    registry.register("a", AguaShader::default(), "ruido_a", ShaderDefaults::default());
+    registry.register("b", HieloShader::default(), "ruido_b", ShaderDefaults::default());
+    registry.register("a", HieloShader::default(), "ruido_c", ShaderDefaults::default());
+
+    assert_eq!(registry.names().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(registry.index_of("a"), Some(0));
+    assert_eq!(registry.get("a").unwrap().noise, "ruido_c");
+    assert!(registry.get("c").is_none());
+}
+
+#[test]
+fn ring_meshes_are_built_once_per_entry() {
+    let registry = planetas();
+    for entry in (0..registry.len()).filter_map(|index| registry.entry(index)) {
+        assert_eq!(entry.defaults.anillos.is_some(), !entry.malla_anillos().is_empty(), "{}", entry.name);
+    }
+}
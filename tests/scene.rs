@@ -0,0 +1,149 @@
+mod common;
+
+use nalgebra_glm::{Mat4, Vec3};
+use lab4::camera::Camera;
+use lab4::registry::planetas;
+use lab4::ruido::presets;
+use lab4::scene::{Orbit, Scene, SceneError, SceneObject, Transform};
+use lab4::scene_file::{self, FileWatcher, SceneDescription};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const EPSILON: f32 = 1e-3;
+
+fn posicion(model_matrix: &Mat4) -> Vec3 {
+    model_matrix.column(3).xyz()
+}
+
+fn escena() -> Scene {
+    let camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+    let mut scene = Scene::new(camera, 0, Vec3::zeros());
+    scene.insert_mesh("esfera", Vec::new());
+    scene
+}
+
+#[test]
+fn moon_orbits_relative_to_its_planet() {
+    let mut scene = escena();
+
+    let mut planeta = SceneObject::new("planeta", "esfera", "agua", Transform { scale: 2.0, ..Transform::default() });
+    planeta.orbit = Some(Orbit::circular(5.0, 100.0, 0.0));
+    planeta.spin_period = 30.0;
+    scene.add(planeta);
+
+    // La luna va antes que su padre para que el orden no importe.
+    let mut luna = SceneObject::new("luna", "esfera", "crateres", Transform::default());
+    luna.parent = Some("planeta".to_string());
+    luna.orbit = Some(Orbit::circular(1.0, 10.0, 0.0));
+    scene.objects.insert(0, luna);
+
+    assert!(scene.validate(&planetas(), &presets()).is_ok());
+
+    for time in [0, 7, 25, 50, 99] {
+        let matrices = scene.model_matrices(time);
+        let (luna, planeta) = (posicion(&matrices[0]), posicion(&matrices[1]));
+
+        assert!((planeta.magnitude() - 5.0).abs() < EPSILON, "t={}: el planeta salio de su orbita", time);
+        // Ni la escala ni el giro del planeta afectan a la distancia de la luna.
+        assert!(((luna - planeta).magnitude() - 1.0).abs() < EPSILON, "t={}: la luna salio de su orbita", time);
+    }
+
+    let inicio = scene.model_matrices(0);
+    let vuelta = scene.model_matrices(100);
+    assert!((posicion(&inicio[1]) - posicion(&vuelta[1])).magnitude() < EPSILON);
+}
+
+#[test]
+fn orbit_inclination_and_eccentricity() {
+    let orbit = Orbit {
+        semi_major_axis: 4.0,
+        eccentricity: 0.5,
+        period: 40.0,
+        inclination: std::f32::consts::FRAC_PI_2,
+        phase: 0.0,
+    };
+
+    // Periapsis y apoapsis a a(1 - e) y a(1 + e) del foco.
+    assert!((orbit.position(0.0) - Vec3::new(2.0, 0.0, 0.0)).magnitude() < EPSILON);
+    assert!((orbit.position(20.0) - Vec3::new(-6.0, 0.0, 0.0)).magnitude() < EPSILON);
+
+    // Con 90 grados de inclinacion la orbita queda en el plano xy.
+    for time in 0..40 {
+        assert!(orbit.position(time as f32).z.abs() < EPSILON);
+    }
+}
+
+#[test]
+fn parent_cycles_are_rejected() {
+    let mut scene = escena();
+    let mut a = SceneObject::new("a", "esfera", "agua", Transform::default());
+    a.parent = Some("b".to_string());
+    let mut b = SceneObject::new("b", "esfera", "agua", Transform::default());
+    b.parent = Some("a".to_string());
+    scene.add(a);
+    scene.add(b);
+
+    assert!(matches!(scene.validate(&planetas(), &presets()), Err(SceneError::Ciclo { .. })));
+    // Sin validar tampoco se cuelga.
+    assert_eq!(scene.model_matrices(0).len(), 2);
+}
+
+fn error_de(texto: &str) -> SceneError {
+    common::error_de(SceneDescription::parse(texto).and_then(|description| description.build(Path::new(""))), texto)
+}
+
+#[test]
+fn shipped_scene_loads() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/escenas/sistema_solar.toml");
+    let scene = scene_file::load(&path).unwrap();
+
+    scene.validate(&planetas(), &presets()).unwrap();
+    assert_eq!(scene.objects.len(), 8);
+    assert!(scene.mesh("esfera").is_some_and(|mesh| !mesh.is_empty()));
+}
+
+#[test]
+fn scene_validation_errors_name_the_field() {
+    let objeto = "[[objects]]\nname = \"a\"\nmesh = \"m\"\nshader = \"agua\"\n";
+    let casos = [
+        ("specular = \"toon\"".to_string(), "specular"),
+        ("[meshes]\nx = \"no_existe.obj\"".to_string(), "meshes.x"),
+        ("[camera]\neye = [0.0, 0.0, 0.0]".to_string(), "camera.eye"),
+        ("[[lights]]\ntype = \"spot\"".to_string(), "lights[0].type"),
+        ("[[lights]]\ntype = \"point\"".to_string(), "lights[0].position"),
+        ("[[lights]]\ntype = \"directional\"\nrange = 3.0".to_string(), "lights[0].range"),
+        (format!("{}scale = 0.0", objeto), "objects[0].scale"),
+        (format!("{}orbit = {{ semi_major_axis = 1.0, eccentricity = 1.0 }}", objeto), "objects[0].orbit.eccentricity"),
+        (format!("{}{}", objeto, objeto), "objects[1].name"),
+    ];
+
+    for (texto, campo_esperado) in casos {
+        match error_de(&texto) {
+            SceneError::Toml(error) => common::assert_campo(&error, campo_esperado, &texto),
+            otro => panic!("se esperaba un error de TOML para {:?}, no {}", texto, otro),
+        }
+    }
+
+    let error = error_de("[noise.malo]\nfrequency = -1.0").to_string();
+    assert!(error.contains("malo") && error.contains("frequency"), "{}", error);
+    let error = error_de("[[objects]]\nname = \"a\"\nmesh = \"m\"\nshader = \"agua\"\nradio = 2.0").to_string();
+    assert!(error.contains("radio"), "{}", error);
+}
+
+#[test]
+fn file_watcher_sees_changes() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join("file_watcher_sees_changes.toml");
+    std::fs::write(&path, "background = 0").unwrap();
+
+    let mut watcher = FileWatcher::new(&path);
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(!watcher.changed());
+
+    std::fs::write(&path, "background = 1").unwrap();
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified + Duration::from_secs(1)).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(watcher.changed());
+    assert!(!watcher.changed());
+}